@@ -0,0 +1,22 @@
+//! Arbitrary bytes into `Proof::<Bls12_381>` deserialization, and every proof that
+//! decodes into the verifier. Neither may panic; malformed bytes must come back as
+//! errors or as a rejected proof.
+#![no_main]
+
+use ark_bls12_381::Bls12_381;
+use ark_groth16::{Groth16, Proof};
+use ark_serialize::CanonicalDeserialize;
+use arklab_fuzz::fixture;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let fixture = fixture();
+    let decoded = [
+        Proof::<Bls12_381>::deserialize_compressed(data),
+        Proof::<Bls12_381>::deserialize_uncompressed(data),
+    ];
+    // Groth16 proofs can be re-randomized, so proofs other than the fixture's may verify
+    for proof in decoded.into_iter().flatten() {
+        let _ = Groth16::<Bls12_381>::verify_proof(&fixture.pvk, &proof, &[fixture.public_input]);
+    }
+});
@@ -0,0 +1,22 @@
+//! Arbitrary bytes into `VerifyingKey::<Bls12_381>` deserialization, and every key that
+//! decodes into `prepare_verifying_key` and the verifier. Keys with the wrong number of
+//! public inputs must be reported as malformed, not panic.
+#![no_main]
+
+use ark_bls12_381::Bls12_381;
+use ark_groth16::{prepare_verifying_key, Groth16, VerifyingKey};
+use ark_serialize::CanonicalDeserialize;
+use arklab_fuzz::fixture;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let fixture = fixture();
+    let decoded = [
+        VerifyingKey::<Bls12_381>::deserialize_compressed(data),
+        VerifyingKey::<Bls12_381>::deserialize_uncompressed(data),
+    ];
+    for vk in decoded.into_iter().flatten() {
+        let pvk = prepare_verifying_key(&vk);
+        let _ = Groth16::<Bls12_381>::verify_proof(&pvk, &fixture.proof, &[fixture.public_input]);
+    }
+});
@@ -0,0 +1,51 @@
+//! Statement the Groth16 fuzz targets verify against.
+//!
+//! The targets only need a verifying key with one public input (like the cubic_expression
+//! and rangeproof examples) and a valid proof for it, so a single-constraint circuit
+//! `x * x = y` is set up once per process with a fixed seed.
+use std::sync::OnceLock;
+
+use ark_bls12_381::{Bls12_381, Fr};
+use ark_groth16::{prepare_verifying_key, Groth16, PreparedVerifyingKey, Proof};
+use ark_relations::{
+    lc,
+    r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError},
+};
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+/// `x * x = y` with `y` public.
+struct SquareCircuit {
+    x: Fr,
+}
+
+impl ConstraintSynthesizer<Fr> for SquareCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        let x = cs.new_witness_variable(|| Ok(self.x))?;
+        let y = cs.new_input_variable(|| Ok(self.x * self.x))?;
+        cs.enforce_constraint(lc!() + x, lc!() + x, lc!() + y)
+    }
+}
+
+pub struct Fixture {
+    pub pvk: PreparedVerifyingKey<Bls12_381>,
+    pub proof: Proof<Bls12_381>,
+    pub public_input: Fr,
+}
+
+/// The prepared key, a valid proof and its public input, created on first use.
+pub fn fixture() -> &'static Fixture {
+    static FIXTURE: OnceLock<Fixture> = OnceLock::new();
+    FIXTURE.get_or_init(|| {
+        let mut rng = StdRng::seed_from_u64(0);
+        let x = Fr::from(3u64);
+        let pk = Groth16::<Bls12_381>::generate_random_parameters_with_reduction(SquareCircuit { x }, &mut rng)
+            .unwrap();
+        let proof = Groth16::<Bls12_381>::create_random_proof_with_reduction(SquareCircuit { x }, &pk, &mut rng)
+            .unwrap();
+        Fixture {
+            pvk: prepare_verifying_key(&pk.vk),
+            proof,
+            public_input: x * x,
+        }
+    })
+}
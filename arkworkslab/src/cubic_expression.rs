@@ -1,19 +1,29 @@
+use labkit::bench::{self, PhaseTimes};
+use arklab::snarkjs;
+use labkit::corpus;
+use labkit::scaling;
+use labkit::memory;
+use labkit::input::{self, CubicInput};
+use labkit::rng::ExampleRng;
+use ark_bn254::{Bn254, Fr};
 use ark_groth16::{prepare_verifying_key, Groth16};
 use ark_crypto_primitives::snark::{CircuitSpecificSetupSNARK, SNARK};
 use ark_ec::pairing::Pairing;
-use ark_ff::{Field, One}; // Import One trait
+use ark_ff::Field;
 use ark_relations::{
     lc,
     r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError, Variable}, // Import Variable
 };
 use ark_serialize::CanonicalSerialize;
-use ark_std::{
-    rand::{RngCore, SeedableRng},
-    test_rng, UniformRand,
-};
+use ark_std::UniformRand;
+use std::path::Path;
 use std::time::Instant;
 
-/// Define a simple circuit that computes x^3 + x + 1 = y
+// Counts the heap usage that `memory::measure` reports
+#[global_allocator]
+static ALLOCATOR: labkit::memory::PeakAllocator = labkit::memory::PeakAllocator;
+
+/// Define a simple circuit that computes x^3 + x + 5 = y
 struct CubicPlusLinearCircuit<F: Field> {
     x: Option<F>,
 }
@@ -23,7 +33,7 @@ impl<ConstraintF: Field> ConstraintSynthesizer<ConstraintF> for CubicPlusLinearC
         self,
         cs: ConstraintSystemRef<ConstraintF>,
     ) -> Result<(), SynthesisError> {
-        // Define variables x, x^2, x^3 and y (i.e. x^3 + x + 1)
+        // Define variables x, x^2, x^3 and y (i.e. x^3 + x + 5)
         let x = cs.new_witness_variable(|| self.x.ok_or(SynthesisError::AssignmentMissing))?;
         let x_squared = cs.new_witness_variable(|| {
             let mut x_val = self.x.ok_or(SynthesisError::AssignmentMissing)?;
@@ -43,18 +53,18 @@ impl<ConstraintF: Field> ConstraintSynthesizer<ConstraintF> for CubicPlusLinearC
             
             let mut result = x_cubed_val; // Initial value is x^3
             result += &self.x.ok_or(SynthesisError::AssignmentMissing)?; // Add x
-            result += ConstraintF::one(); // Add 1
+            result += ConstraintF::from(5u64); // Add 5, as in snarkjs/cubic_*/cubic_expression.circom
             Ok(result) // Return y
         })?;
 
-        // Add constraints: x * x = x^2, x^2 * x = x^3, and x^3 + x + 1 = y
+        // Add constraints: x * x = x^2, x^2 * x = x^3, and x^3 + x + 5 = y
         cs.enforce_constraint(lc!() + x, lc!() + x, lc!() + x_squared)?; // x * x = x^2
         cs.enforce_constraint(lc!() + x_squared, lc!() + x, lc!() + x_cubed)?; // x^2 * x = x^3
         cs.enforce_constraint(
-            lc!() + x_cubed + x + (ConstraintF::one(), Variable::One), // x^3 + x + 1
+            lc!() + x_cubed + x + (ConstraintF::from(5u64), Variable::One), // x^3 + x + 5
             lc!() + Variable::One, // 1 (no multiplication needed)
             lc!() + y,
-        )?; // y = x^3 + x + 1
+        )?; // y = x^3 + x + 5
 
         // Print number of constraints
         println!("Number of constraints: {}", cs.num_constraints());
@@ -64,19 +74,53 @@ impl<ConstraintF: Field> ConstraintSynthesizer<ConstraintF> for CubicPlusLinearC
 
 pub fn main() {
     // Use BLS12-381 elliptic curve
-    test_prove_and_verify::<ark_bls12_381::Bls12_381>();
+    if let Some(config) = bench::BenchConfig::from_args() {
+        bench::run("cubic_expression", &config, test_prove_and_verify::<ark_bls12_381::Bls12_381>);
+    } else if let Some(dir) = snarkjs::dir_from_args() {
+        export_snarkjs(&dir);
+    } else if let Some(counts) = scaling::thread_counts_from_args() {
+        scaling::sweep("cubic_expression", &counts, || test_prove_and_verify::<ark_bls12_381::Bls12_381>().prove);
+    } else {
+        test_prove_and_verify::<ark_bls12_381::Bls12_381>();
+    }
+}
+
+/// Proves the statement on BN254, the `bn128` of snarkjs, and writes the verifying key,
+/// the public signals and the proof to `dir` as snarkjs JSON
+fn export_snarkjs(dir: &Path) {
+    let mut rng = ExampleRng::from_args();
+    let (pk, vk) = Groth16::<Bn254>::setup(CubicPlusLinearCircuit { x: None }, &mut rng).unwrap();
+    let x = match input::from_args::<CubicInput>() {
+        Some(input) => Fr::from(input.x),
+        None => Fr::rand(&mut rng),
+    };
+    let y = x * x * x + x + Fr::from(5u64);
+    let proof = Groth16::<Bn254>::prove(&pk, CubicPlusLinearCircuit { x: Some(x) }, &mut rng).unwrap();
+    println!("Proof is valid: {}", Groth16::<Bn254>::verify(&vk, &[y], &proof).unwrap());
+
+    snarkjs::export(dir, &vk, &[y], &proof).expect("cannot write snarkjs files");
+    println!("Wrote verification_key.json, public.json and proof.json to {}", dir.display());
+    println!(
+        "Check them with: snarkjs groth16 verify {0}/verification_key.json {0}/public.json {0}/proof.json",
+        dir.display()
+    );
 }
 
-/// Proof and verification function
-fn test_prove_and_verify<E>()
+/// Proof and verification function, returns the time of each phase
+fn test_prove_and_verify<E>() -> PhaseTimes
 where
     E: Pairing,
 {
     // Create random number generator
-    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+    // OS randomness, or a reproducible stream with `--seed <n>`
+    let mut rng = ExampleRng::from_args();
 
     // Execute Groth16 setup (generate proving key and verifying key)
-    let (pk, vk) = Groth16::<E>::setup(CubicPlusLinearCircuit { x: None }, &mut rng).unwrap();
+    let start0 = Instant::now();
+    let ((pk, vk), _) = memory::measure("Setup", || {
+        Groth16::<E>::setup(CubicPlusLinearCircuit { x: None }, &mut rng).unwrap()
+    });
+    let setup_time = start0.elapsed();
     
     let pvk = prepare_verifying_key::<E>(&vk);
 
@@ -87,29 +131,45 @@ where
     println!("Uncompressed vk size: {} bytes", vk_size);
     println!("Total uncompressed size (pk + vk): {} bytes", pk_size + vk_size);
 
-    // Generate random x
-    let x = E::ScalarField::rand(&mut rng);
+    // x from `--input <file>` (the schema of snarkjs/cubic_*/input.json), or a random x
+    let x = match input::from_args::<CubicInput>() {
+        Some(input) => E::ScalarField::from(input.x),
+        None => E::ScalarField::rand(&mut rng),
+    };
     let mut y = x;
     y.square_in_place(); // Calculate x^2
     let x_cubed = y * x; // Calculate x^3
-    y = x_cubed + x + E::ScalarField::one(); // Calculate y = x^3 + x + 1
+    y = x_cubed + x + E::ScalarField::from(5u64); // Calculate y = x^3 + x + 5
 
     let start1 = Instant::now();
-    // Use Groth16 to prove x^3 + x + 1 = y
-    let proof = Groth16::<E>::prove(
-        &pk,
-        CubicPlusLinearCircuit { x: Some(x) },
-        &mut rng,
-    )
-    .unwrap();
+    // Use Groth16 to prove x^3 + x + 5 = y
+    let (proof, _) = memory::measure("Prove", || {
+        Groth16::<E>::prove(
+            &pk,
+            CubicPlusLinearCircuit { x: Some(x) },
+            &mut rng,
+        )
+        .unwrap()
+    });
     let start2 = Instant::now();
     // Output uncompressed proof size
     let uncompressed_size = proof.uncompressed_size();
     println!("Uncompressed proof size: {} bytes", uncompressed_size);
     // Verify the proof
-    let is_valid = Groth16::<E>::verify_with_processed_vk(&pvk, &[y], &proof).unwrap();
+    let (is_valid, _) = memory::measure("Verify", || {
+        Groth16::<E>::verify_with_processed_vk(&pvk, &[y], &proof).unwrap()
+    });
     let start3 = Instant::now();
     println!("Proof is valid: {}", is_valid); // Print proof result
+
+    // `--save-corpus <dir>`: seed the fuzz targets with this key and proof
+    if corpus::dir_from_args().is_some() {
+        let (mut vk_bytes, mut proof_bytes) = (Vec::new(), Vec::new());
+        vk.serialize_compressed(&mut vk_bytes).unwrap();
+        proof.serialize_compressed(&mut proof_bytes).unwrap();
+        corpus::save_from_args("groth16_verifying_key", "cubic_expression", &vk_bytes);
+        corpus::save_from_args("groth16_proof", "cubic_expression", &proof_bytes);
+    }
     let duration1 = start2.duration_since(start1);
     let duration2 = start3.duration_since(start2);
 
@@ -117,4 +177,90 @@ where
     let millis2 = duration2.as_secs_f64() * 1000.0;
     println!("Prove time: {:.3} milliseconds", millis1);
     println!("Verify time: {:.3} milliseconds", millis2);
+
+    PhaseTimes {
+        setup: setup_time,
+        prove: start2.duration_since(start1),
+        verify: start3.duration_since(start2),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_relations::r1cs::ConstraintSystem;
+    use ark_groth16::Proof;
+    use ark_serialize::CanonicalDeserialize;
+    use labkit::hiding::{self, Hiding};
+    use labkit::reference::{self, CUBIC_X};
+    use labkit::tamper;
+
+    #[test]
+    fn test_output_matches_reference() {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        CubicPlusLinearCircuit { x: Some(Fr::from(CUBIC_X)) }
+            .generate_constraints(cs.clone())
+            .unwrap();
+        assert!(cs.is_satisfied().unwrap());
+        // The first instance variable is the constant one, the second is y
+        let y = cs.borrow().unwrap().instance_assignment[1];
+        assert_eq!(y, Fr::from(reference::cubic(CUBIC_X)));
+    }
+
+    #[test]
+    fn test_wrong_outputs_are_rejected() {
+        let mut rng = ExampleRng::new(Some(0));
+        let (pk, vk) = Groth16::<Bls12_381>::setup(CubicPlusLinearCircuit { x: None }, &mut rng).unwrap();
+        let pvk = prepare_verifying_key(&vk);
+        let proof = Groth16::<Bls12_381>::prove(&pk, CubicPlusLinearCircuit { x: Some(Fr::from(CUBIC_X)) }, &mut rng)
+            .unwrap();
+
+        let verify = |y: u64| Groth16::<Bls12_381>::verify_with_processed_vk(&pvk, &[Fr::from(y)], &proof).unwrap();
+        assert!(verify(reference::cubic(CUBIC_X)));
+        for y in reference::wrong_cubic_outputs() {
+            assert!(!verify(y), "y = {} should be rejected", y);
+        }
+    }
+
+    #[test]
+    fn test_tampered_proofs_are_rejected() {
+        let mut rng = ExampleRng::new(Some(0));
+        let (pk, vk) = Groth16::<Bls12_381>::setup(CubicPlusLinearCircuit { x: None }, &mut rng).unwrap();
+        let pvk = prepare_verifying_key(&vk);
+        let mut prove = |x: u64| {
+            let proof = Groth16::<Bls12_381>::prove(&pk, CubicPlusLinearCircuit { x: Some(Fr::from(x)) }, &mut rng)
+                .unwrap();
+            let mut bytes = Vec::new();
+            proof.serialize_compressed(&mut bytes).unwrap();
+            bytes
+        };
+        let proof = prove(CUBIC_X);
+        let other = prove(CUBIC_X + 1);
+
+        let y = Fr::from(reference::cubic(CUBIC_X));
+        tamper::assert_rejects_tampering(&proof, &other, |bytes| {
+            Proof::<Bls12_381>::deserialize_compressed(bytes).is_ok_and(|proof| {
+                Groth16::<Bls12_381>::verify_with_processed_vk(&pvk, &[y], &proof).unwrap_or(false)
+            })
+        });
+    }
+
+    #[test]
+    fn test_proofs_are_randomized() {
+        let mut rng = ExampleRng::new(Some(0));
+        let (pk, _) = Groth16::<Bls12_381>::setup(CubicPlusLinearCircuit { x: None }, &mut rng).unwrap();
+        let prove = |rng: &mut ExampleRng| {
+            let proof = Groth16::<Bls12_381>::prove(&pk, CubicPlusLinearCircuit { x: Some(Fr::from(CUBIC_X)) }, rng)
+                .unwrap();
+            let mut bytes = Vec::new();
+            proof.serialize_compressed(&mut bytes).unwrap();
+            bytes
+        };
+
+        // Groth16 draws r and s for every proof, from the OS by default
+        assert_eq!(hiding::check(|| prove(&mut ExampleRng::new(None))), Hiding::Randomized);
+        // `--seed` replays the same r and s, so seeded runs repeat their proofs
+        assert_eq!(hiding::check(|| prove(&mut ExampleRng::new(Some(1)))), Hiding::Deterministic);
+    }
 }
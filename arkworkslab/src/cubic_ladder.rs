@@ -0,0 +1,152 @@
+use labkit::bench::{PhaseTimes, SizedRun};
+use labkit::ladder;
+use labkit::memory;
+use labkit::rng::ExampleRng;
+use ark_groth16::{prepare_verifying_key, Groth16};
+use ark_crypto_primitives::snark::{CircuitSpecificSetupSNARK, SNARK};
+use ark_ec::pairing::Pairing;
+use ark_ff::{Field, PrimeField};
+use ark_relations::{
+    lc,
+    r1cs::{ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef, SynthesisError, Variable},
+};
+use ark_serialize::CanonicalSerialize;
+use ark_std::UniformRand;
+use std::time::Instant;
+
+// Counts the heap usage that `memory::measure` reports
+#[global_allocator]
+static ALLOCATOR: labkit::memory::PeakAllocator = labkit::memory::PeakAllocator;
+
+/// Chains `x_{i+1} = x_i^3 + x_i + 5` for `steps` steps, with `x_0` private and
+/// `x_steps` public. Every step costs two constraints.
+struct CubicLadderCircuit<F: Field> {
+    x0: Option<F>,
+    steps: usize,
+}
+
+/// Computes `x_steps` natively.
+fn ladder_output<F: PrimeField>(x0: F, steps: usize) -> F {
+    (0..steps).fold(x0, |x, _| x.square() * x + x + F::from(ladder::STEP_CONSTANT))
+}
+
+impl<ConstraintF: PrimeField> ConstraintSynthesizer<ConstraintF> for CubicLadderCircuit<ConstraintF> {
+    fn generate_constraints(
+        self,
+        cs: ConstraintSystemRef<ConstraintF>,
+    ) -> Result<(), SynthesisError> {
+        let five = ConstraintF::from(ladder::STEP_CONSTANT);
+        let mut x_val = self.x0;
+        let mut x = cs.new_witness_variable(|| x_val.ok_or(SynthesisError::AssignmentMissing))?;
+
+        for i in 0..self.steps {
+            let x_squared_val = x_val.map(|x| x.square());
+            let next_val = x_val.map(|x| x.square() * x + x + five);
+
+            let x_squared = cs.new_witness_variable(|| x_squared_val.ok_or(SynthesisError::AssignmentMissing))?;
+            // The last value is the public output
+            let next = if i + 1 == self.steps {
+                cs.new_input_variable(|| next_val.ok_or(SynthesisError::AssignmentMissing))?
+            } else {
+                cs.new_witness_variable(|| next_val.ok_or(SynthesisError::AssignmentMissing))?
+            };
+
+            cs.enforce_constraint(lc!() + x, lc!() + x, lc!() + x_squared)?; // x * x = x^2
+            // x^2 * x = x_{i+1} - x - 5
+            cs.enforce_constraint(
+                lc!() + x_squared,
+                lc!() + x,
+                lc!() + next - x - (five, Variable::One),
+            )?;
+
+            x = next;
+            x_val = next_val;
+        }
+        Ok(())
+    }
+}
+
+fn main() {
+    let log_sizes = ladder::log_sizes_from_args();
+    ladder::sweep("cubic_ladder", "constraints", &log_sizes, |steps| {
+        prove_and_verify::<ark_bls12_381::Bls12_381>(steps)
+    });
+}
+
+/// Runs Groth16 setup, proving and verification for a ladder of `steps` steps.
+fn prove_and_verify<E>(steps: usize) -> SizedRun
+where
+    E: Pairing,
+{
+    // OS randomness, or a reproducible stream with `--seed <n>`
+    let mut rng = ExampleRng::from_args();
+
+    let cs = ConstraintSystem::<E::ScalarField>::new_ref();
+    CubicLadderCircuit { x0: Some(E::ScalarField::from(3u64)), steps }
+        .generate_constraints(cs.clone())
+        .expect("constraint generation failed");
+    let num_constraints = cs.num_constraints();
+    println!("Number of constraints: {}", num_constraints);
+
+    let start0 = Instant::now();
+    let ((pk, vk), _) = memory::measure("Setup", || {
+        Groth16::<E>::setup(CubicLadderCircuit { x0: None, steps }, &mut rng).unwrap()
+    });
+    let setup_time = start0.elapsed();
+    let pvk = prepare_verifying_key::<E>(&vk);
+
+    let x0 = E::ScalarField::rand(&mut rng);
+    let y = ladder_output(x0, steps);
+
+    let start1 = Instant::now();
+    let (proof, prove_memory) = memory::measure("Prove", || {
+        Groth16::<E>::prove(&pk, CubicLadderCircuit { x0: Some(x0), steps }, &mut rng).unwrap()
+    });
+    let start2 = Instant::now();
+
+    let (is_valid, _) = memory::measure("Verify", || {
+        Groth16::<E>::verify_with_processed_vk(&pvk, &[y], &proof).unwrap()
+    });
+    let start3 = Instant::now();
+    assert!(is_valid, "proof should verify");
+    println!("Proof is valid: {}", is_valid);
+
+    SizedRun {
+        size: num_constraints,
+        times: PhaseTimes {
+            setup: setup_time,
+            prove: start2.duration_since(start1),
+            verify: start3.duration_since(start2),
+        },
+        proof_size: proof.compressed_size(),
+        prove_memory,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::{Bls12_381, Fr};
+
+    #[test]
+    fn test_ladder_proves_its_output() {
+        let steps = ladder::steps(4);
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        CubicLadderCircuit { x0: Some(Fr::from(3u64)), steps }
+            .generate_constraints(cs.clone())
+            .unwrap();
+        assert!(cs.is_satisfied().unwrap());
+        assert_eq!(cs.num_constraints(), 1 << 4);
+
+        let mut rng = ExampleRng::new(Some(0));
+        let (pk, vk) = Groth16::<Bls12_381>::setup(CubicLadderCircuit { x0: None, steps }, &mut rng).unwrap();
+        let pvk = prepare_verifying_key(&vk);
+        let x0 = Fr::from(3u64);
+        let proof = Groth16::<Bls12_381>::prove(&pk, CubicLadderCircuit { x0: Some(x0), steps }, &mut rng).unwrap();
+
+        let y = ladder_output(x0, steps);
+        let verify = |y: Fr| Groth16::<Bls12_381>::verify_with_processed_vk(&pvk, &[y], &proof).unwrap();
+        assert!(verify(y));
+        assert!(!verify(y + Fr::from(1u64)), "a wrong x_N should be rejected");
+    }
+}
@@ -0,0 +1,2 @@
+pub mod snarkjs;
+pub mod zkey;
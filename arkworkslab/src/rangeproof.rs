@@ -1,3 +1,9 @@
+use labkit::bench::{self, PhaseTimes};
+use labkit::corpus;
+use labkit::scaling;
+use labkit::memory;
+use labkit::input::{self, RangeInput};
+use labkit::rng::ExampleRng;
 use ark_groth16::{prepare_verifying_key, Groth16};
 use ark_crypto_primitives::snark::{CircuitSpecificSetupSNARK, SNARK};
 use ark_ec::pairing::Pairing;
@@ -7,12 +13,13 @@ use ark_relations::{
     lc,
     r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError,Variable},
 };
-use ark_std::{
-    rand::{RngCore, SeedableRng},
-    test_rng, 
-};
+use ark_std::rand::RngCore;
 use std::time::Instant;
 
+// Counts the heap usage that `memory::measure` reports
+#[global_allocator]
+static ALLOCATOR: labkit::memory::PeakAllocator = labkit::memory::PeakAllocator;
+
 /// Define a circuit to prove x is in range [0, 2^32]
 struct RangeProofCircuit<F: Field> {
     x: Option<F>,
@@ -50,7 +57,7 @@ impl<ConstraintF: PrimeField> ConstraintSynthesizer<ConstraintF> for RangeProofC
         let mut lc = lc!();
         let mut coeff = ConstraintF::one();
         for bit in bits.iter() {
-            lc = lc + (coeff, *bit);
+            lc += (coeff, *bit);
             coeff = coeff.double();
         }
         
@@ -68,18 +75,29 @@ impl<ConstraintF: PrimeField> ConstraintSynthesizer<ConstraintF> for RangeProofC
 }
 
 fn main() {
-    test_prove_and_verify::<ark_bls12_381::Bls12_381>();
+    if let Some(config) = bench::BenchConfig::from_args() {
+        bench::run("rangeproof", &config, test_prove_and_verify::<ark_bls12_381::Bls12_381>);
+    } else if let Some(counts) = scaling::thread_counts_from_args() {
+        scaling::sweep("rangeproof", &counts, || test_prove_and_verify::<ark_bls12_381::Bls12_381>().prove);
+    } else {
+        test_prove_and_verify::<ark_bls12_381::Bls12_381>();
+    }
 }
 
-/// Proof and verification function
-fn test_prove_and_verify<E>()
+/// Proof and verification function, returns the time of each phase
+fn test_prove_and_verify<E>() -> PhaseTimes
 where
     E: Pairing,
 {
-    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+    // OS randomness, or a reproducible stream with `--seed <n>`
+    let mut rng = ExampleRng::from_args();
 
     // Setup circuit
-    let (pk, vk) = Groth16::<E>::setup(RangeProofCircuit { x: None }, &mut rng).unwrap();
+    let start0 = Instant::now();
+    let ((pk, vk), _) = memory::measure("Setup", || {
+        Groth16::<E>::setup(RangeProofCircuit { x: None }, &mut rng).unwrap()
+    });
+    let setup_time = start0.elapsed();
 
     let pvk = prepare_verifying_key::<E>(&vk);
 
@@ -90,25 +108,46 @@ where
     println!("Uncompressed vk size: {} bytes", vk_size);
     println!("Total uncompressed size (pk + vk): {} bytes", pk_size + vk_size);
 
-    // Generate a random number in range [0, 2^32]
-    let x = E::ScalarField::from(rng.next_u32() as u64);
+    // x from `--input <file>` (the schema of snarkjs/rangeproof_*/input.json), or a
+    // random number in range [0, 2^32)
+    let x = match input::from_args::<RangeInput>() {
+        Some(input) if input.bits != 32 => {
+            eprintln!("error: the circuit proves 32-bit ranges, the input asks for {} bits", input.bits);
+            std::process::exit(1);
+        }
+        Some(input) => E::ScalarField::from(input.value),
+        None => E::ScalarField::from(rng.next_u32() as u64),
+    };
 
     let start1 = Instant::now();
     // Generate proof
-    let proof = Groth16::<E>::prove(
-        &pk,
-        RangeProofCircuit { x: Some(x) },
-        &mut rng,
-    )
-    .unwrap();
+    let (proof, _) = memory::measure("Prove", || {
+        Groth16::<E>::prove(
+            &pk,
+            RangeProofCircuit { x: Some(x) },
+            &mut rng,
+        )
+        .unwrap()
+    });
     let start2 = Instant::now();
     // Output uncompressed proof size
     let uncompressed_size = proof.uncompressed_size();
     println!("Uncompressed proof size: {} bytes", uncompressed_size);
     // Verify the proof
-    let is_valid = Groth16::<E>::verify_with_processed_vk(&pvk, &[x], &proof).unwrap();
+    let (is_valid, _) = memory::measure("Verify", || {
+        Groth16::<E>::verify_with_processed_vk(&pvk, &[x], &proof).unwrap()
+    });
     let start3 = Instant::now();
     println!("Proof is valid: {}", is_valid); // Print proof result
+
+    // `--save-corpus <dir>`: seed the fuzz targets with this key and proof
+    if corpus::dir_from_args().is_some() {
+        let (mut vk_bytes, mut proof_bytes) = (Vec::new(), Vec::new());
+        vk.serialize_compressed(&mut vk_bytes).unwrap();
+        proof.serialize_compressed(&mut proof_bytes).unwrap();
+        corpus::save_from_args("groth16_verifying_key", "rangeproof", &vk_bytes);
+        corpus::save_from_args("groth16_proof", "rangeproof", &proof_bytes);
+    }
     let duration1 = start2.duration_since(start1);
     let duration2 = start3.duration_since(start2);
 
@@ -116,4 +155,73 @@ where
     let millis2 = duration2.as_secs_f64() * 1000.0;
     println!("Prove time: {:.3} milliseconds", millis1);
     println!("Verify time: {:.3} milliseconds", millis2);
+
+    PhaseTimes {
+        setup: setup_time,
+        prove: start2.duration_since(start1),
+        verify: start3.duration_since(start2),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_relations::r1cs::ConstraintSystem;
+    use ark_groth16::Proof;
+    use ark_serialize::CanonicalDeserialize;
+    use labkit::reference::{self, RANGE_VALUE};
+    use labkit::tamper;
+
+    fn is_satisfied(value: u64) -> bool {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        RangeProofCircuit { x: Some(Fr::from(value)) }
+            .generate_constraints(cs.clone())
+            .unwrap();
+        cs.is_satisfied().unwrap()
+    }
+
+    #[test]
+    fn test_value_in_range_is_accepted() {
+        assert_eq!(reference::RANGE_BITS, 32);
+        assert!(is_satisfied(RANGE_VALUE));
+
+        let mut rng = ExampleRng::new(Some(0));
+        let (pk, vk) = Groth16::<Bls12_381>::setup(RangeProofCircuit { x: None }, &mut rng).unwrap();
+        let x = Fr::from(RANGE_VALUE);
+        let proof = Groth16::<Bls12_381>::prove(&pk, RangeProofCircuit { x: Some(x) }, &mut rng).unwrap();
+        assert!(Groth16::<Bls12_381>::verify_with_processed_vk(&prepare_verifying_key(&vk), &[x], &proof).unwrap());
+    }
+
+    // The Groth16 prover asserts that the witness satisfies the circuit, so invalid
+    // witnesses are checked on the constraint system alone
+    #[test]
+    fn test_out_of_range_values_are_rejected() {
+        for value in reference::OUT_OF_RANGE_VALUES {
+            assert!(!is_satisfied(value), "{} should not satisfy the circuit", value);
+        }
+    }
+
+    #[test]
+    fn test_tampered_proofs_are_rejected() {
+        let mut rng = ExampleRng::new(Some(0));
+        let (pk, vk) = Groth16::<Bls12_381>::setup(RangeProofCircuit { x: None }, &mut rng).unwrap();
+        let pvk = prepare_verifying_key(&vk);
+        let mut prove = |value: u64| {
+            let proof = Groth16::<Bls12_381>::prove(&pk, RangeProofCircuit { x: Some(Fr::from(value)) }, &mut rng)
+                .unwrap();
+            let mut bytes = Vec::new();
+            proof.serialize_compressed(&mut bytes).unwrap();
+            bytes
+        };
+        let proof = prove(RANGE_VALUE);
+        let other = prove(RANGE_VALUE + 1);
+
+        let x = Fr::from(RANGE_VALUE);
+        tamper::assert_rejects_tampering(&proof, &other, |bytes| {
+            Proof::<Bls12_381>::deserialize_compressed(bytes).is_ok_and(|proof| {
+                Groth16::<Bls12_381>::verify_with_processed_vk(&pvk, &[x], &proof).unwrap_or(false)
+            })
+        });
+    }
 }
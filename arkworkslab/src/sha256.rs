@@ -1,17 +1,21 @@
+use labkit::bench::{self, PhaseTimes};
+use labkit::corpus;
+use labkit::scaling;
+use labkit::memory;
+use labkit::input::{self, Sha256Input};
+use labkit::rng::ExampleRng;
+use labkit::bench::SizedRun;
 use ark_groth16::{Groth16, prepare_verifying_key};
 use ark_ec::pairing::Pairing;
-use ark_ff::{Field, PrimeField};
-use ark_relations::{r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError}};
+use ark_ff::{Field, PrimeField, ToConstraintField};
+use ark_relations::{r1cs::{ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef, SynthesisError}};
+use ark_serialize::CanonicalSerialize;
 use ark_std::{
-    rand::{RngCore, SeedableRng},
-    test_rng,
     vec::Vec,
-    Zero,
     time::Instant,
 };
 use ark_crypto_primitives::crh::sha256::constraints::{Sha256Gadget, UnitVar};
 use ark_r1cs_std::prelude::*;
-use rand_chacha::ChaChaRng;
 use sha2::{Sha256, Digest};
 use ark_bls12_381::Bls12_381;
 use std::marker::PhantomData;
@@ -20,10 +24,14 @@ use ark_crypto_primitives::{
     snark::{SNARK, CircuitSpecificSetupSNARK},
 };
 
+// Counts the heap usage that `memory::measure` reports
+#[global_allocator]
+static ALLOCATOR: labkit::memory::PeakAllocator = labkit::memory::PeakAllocator;
+
 // SHA256 Circuit Definition
 struct Sha256Circuit<ConstraintF: Field> {
     preimage: Option<Vec<u8>>,  // Input to be hashed
-    hash: Option<Vec<u8>>,      // Expected hash value to verify
+    hash: Option<Vec<u8>>,      // Expected hash value, the public input
     _phantom: PhantomData<ConstraintF>,
 }
 
@@ -32,13 +40,14 @@ impl<ConstraintF: PrimeField> ConstraintSynthesizer<ConstraintF> for Sha256Circu
         self,
         cs: ConstraintSystemRef<ConstraintF>,
     ) -> Result<(), SynthesisError> {
-        // Modification: Make hash value a private input instead of public input
         let preimage_var = UInt8::new_witness_vec(
             ark_relations::ns!(cs, "preimage"),
             self.preimage.as_deref().unwrap_or(&[]),
         )?;
 
-        let hash_var = UInt8::new_witness_vec(  // Changed to witness instead of input
+        // The digest is public: `new_input_vec` packs its bytes into field elements,
+        // which the verifier recomputes with `digest_inputs`
+        let hash_var = UInt8::new_input_vec(
             ark_relations::ns!(cs, "hash"),
             self.hash.as_deref().unwrap_or(&[]),
         )?;
@@ -58,35 +67,67 @@ impl<ConstraintF: PrimeField> ConstraintSynthesizer<ConstraintF> for Sha256Circu
     }
 }
 
+/// The public inputs for `digest`, packed as `UInt8::new_input_vec` allocates them.
+fn digest_inputs<F: PrimeField>(digest: &[u8]) -> Vec<F> {
+    digest.to_field_elements().expect("bytes always pack into field elements")
+}
+
 fn main() {
-    // Test on BLS12-381 curve
-    test_prove_and_verify::<Bls12_381>();
+    // Test on BLS12-381 curve, with the message from `--input <file>` (the schema of
+    // snarkjs/sha256_*/input.json) if given
+    let preimage = input::from_args::<Sha256Input>().map_or(b"Hello, World!".to_vec(), |input| input.message);
+    let preimage = preimage.as_slice();
+    if let Some(config) = bench::BenchConfig::from_args() {
+        bench::run("sha256", &config, || test_prove_and_verify::<Bls12_381>(preimage).times);
+    } else if let Some(lengths) = scaling::input_lengths_from_args() {
+        scaling::input_size_sweep("sha256", "constraints", &lengths, |len| {
+            test_prove_and_verify::<Bls12_381>(&scaling::sample_message(len))
+        });
+    } else if let Some(counts) = scaling::thread_counts_from_args() {
+        scaling::sweep("sha256", &counts, || test_prove_and_verify::<Bls12_381>(preimage).times.prove);
+    } else {
+        test_prove_and_verify::<Bls12_381>(preimage);
+    }
 }
 
-fn test_prove_and_verify<E>()
+/// Proves and verifies the hash of `preimage`, returns the time of each phase
+/// together with the circuit size, proof size and prover memory
+fn test_prove_and_verify<E>(preimage: &[u8]) -> SizedRun
 where
     E: Pairing,
 {
-    let mut rng = test_rng();
-    let mut prover_rng = ChaChaRng::seed_from_u64(rng.next_u32() as u64);
+    // OS randomness, or a reproducible stream with `--seed <n>`
+    let mut prover_rng = ExampleRng::from_args();
 
     // Setup input data
-    let preimage = b"Hello, World!".to_vec();
+    let preimage = preimage.to_vec();
     let mut hasher = Sha256::new();
     hasher.update(&preimage);
     let hash = hasher.finalize().to_vec();
 
     println!("Setting up circuit...");
-    // Setup circuit
-    let setup_circuit = Sha256Circuit {
-        preimage: None,
-        hash: None,
+    // Setup circuit. The number of constraints depends on the preimage length, so the
+    // setup circuit uses a dummy preimage and hash of the same lengths.
+    let dummy_circuit = || Sha256Circuit {
+        preimage: Some(vec![0; preimage.len()]),
+        hash: Some(vec![0; hash.len()]),
         _phantom: PhantomData,
     };
+    let setup_circuit = dummy_circuit();
+    let cs = ConstraintSystem::<E::ScalarField>::new_ref();
+    dummy_circuit()
+        .generate_constraints(cs.clone())
+        .expect("constraint generation failed");
+    let num_constraints = cs.num_constraints();
+    println!("Preimage length: {} bytes, number of constraints: {}", preimage.len(), num_constraints);
 
     println!("Generating proving key and verifying key...");
-    let (pk, vk) = Groth16::<E>::setup(setup_circuit, &mut prover_rng)
-        .expect("Setup failed");
+    let start0 = Instant::now();
+    let ((pk, vk), _) = memory::measure("Setup", || {
+        Groth16::<E>::setup(setup_circuit, &mut prover_rng)
+            .expect("Setup failed")
+    });
+    let setup_time = start0.elapsed();
 
     println!("Setting up proving circuit...");
     let proving_circuit = Sha256Circuit {
@@ -97,36 +138,26 @@ where
 
     println!("Generating proof...");
     let start1 = Instant::now();
-    let proof = Groth16::<E>::prove(&pk, proving_circuit, &mut prover_rng)
-        .expect("Proving failed");
+    let (proof, prove_memory) = memory::measure("Prove", || {
+        Groth16::<E>::prove(&pk, proving_circuit, &mut prover_rng)
+            .expect("Proving failed")
+    });
     let start2 = Instant::now();
 
     println!("Processing verification key...");
     let pvk = prepare_verifying_key(&vk);
-    // Modification: Split 32 bytes into 4 groups of 8 bytes each
-    let mut combined_hash = E::ScalarField::zero();
-    for (i, chunk) in hash.chunks(8).enumerate() {
-        let mut chunk_value = E::ScalarField::zero();
-        for (j, &byte) in chunk.iter().enumerate() {
-            chunk_value += E::ScalarField::from(byte as u64) * E::ScalarField::from(1u64 << (8 * j));
-        }
-        if i > 0 {
-            chunk_value = chunk_value * E::ScalarField::from(1u64 << 63) * E::ScalarField::from(2u64);
-        }
-        combined_hash += chunk_value;
-    }
-    
-    let public_inputs = vec![combined_hash];
+    let public_inputs = digest_inputs::<E::ScalarField>(&hash);
 
     println!("\nVerifying proof...");
-    let verification_result = Groth16::<E>::verify_with_processed_vk(
-        &pvk,
-        &[],  // Empty public inputs
-        &proof,
-    );
+    let (verification_result, _) = memory::measure("Verify", || {
+        Groth16::<E>::verify_with_processed_vk(
+            &pvk,
+            &public_inputs,
+            &proof,
+        )
+    });
     let start3 = Instant::now();
 
-    // Modification: Access gamma_abc_g1 through pvk.vk
     match &verification_result {
         Ok(valid) => println!("Verification completed with result: {}", valid),
         Err(e) => {
@@ -140,7 +171,7 @@ where
     // Calculate timing and output results
     let prove_time = start2.duration_since(start1).as_secs_f64() * 1000.0;
     let verify_time = start3.duration_since(start2).as_secs_f64() * 1000.0;
-    let proof_size = std::mem::size_of_val(&proof);
+    let proof_size = proof.compressed_size();
 
     println!("Prove time: {:.3} ms", prove_time);
     println!("Verify time: {:.3} ms", verify_time);
@@ -150,4 +181,107 @@ where
         Ok(is_valid) => println!("Proof verification result: {}", is_valid),
         Err(e) => println!("Verification failed with error: {:?}", e),
     }
-}
\ No newline at end of file
+
+    // `--save-corpus <dir>`: seed the fuzz targets with this key and proof
+    if corpus::dir_from_args().is_some() {
+        let (mut vk_bytes, mut proof_bytes) = (Vec::new(), Vec::new());
+        vk.serialize_compressed(&mut vk_bytes).unwrap();
+        proof.serialize_compressed(&mut proof_bytes).unwrap();
+        corpus::save_from_args("groth16_verifying_key", "sha256", &vk_bytes);
+        corpus::save_from_args("groth16_proof", "sha256", &proof_bytes);
+    }
+
+    SizedRun {
+        size: num_constraints,
+        times: PhaseTimes {
+            setup: setup_time,
+            prove: start2.duration_since(start1),
+            verify: start3.duration_since(start2),
+        },
+        proof_size,
+        prove_memory,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::{Fr, G1Affine};
+    use ark_ec::{AffineRepr, CurveGroup};
+    use ark_groth16::Proof;
+    use ark_serialize::CanonicalDeserialize;
+    use labkit::reference::{self, SHA256_MESSAGE};
+    use labkit::tamper;
+
+    fn is_satisfied(hash: &[u8]) -> bool {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        Sha256Circuit {
+            preimage: Some(SHA256_MESSAGE.to_vec()),
+            hash: Some(hash.to_vec()),
+            _phantom: PhantomData,
+        }
+        .generate_constraints(cs.clone())
+        .unwrap();
+        cs.is_satisfied().unwrap()
+    }
+
+    #[test]
+    fn test_digest_matches_reference() {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let message = UInt8::new_witness_vec(cs.clone(), SHA256_MESSAGE).unwrap();
+        let digest = Sha256Gadget::<Fr>::evaluate(&UnitVar::default(), &message).unwrap();
+        assert_eq!(digest.0.value().unwrap(), reference::sha256(SHA256_MESSAGE));
+        assert!(is_satisfied(&reference::sha256(SHA256_MESSAGE)));
+    }
+
+    #[test]
+    fn test_wrong_digests_are_rejected() {
+        for digest in reference::wrong_digests() {
+            assert!(!is_satisfied(&digest));
+        }
+    }
+
+    fn circuit(preimage: &[u8]) -> Sha256Circuit<Fr> {
+        Sha256Circuit {
+            preimage: Some(preimage.to_vec()),
+            hash: Some(reference::sha256(preimage).to_vec()),
+            _phantom: PhantomData,
+        }
+    }
+
+    #[test]
+    fn test_tampered_proofs_are_rejected() {
+        let mut rng = ExampleRng::new(Some(0));
+        let (pk, vk) = Groth16::<Bls12_381>::setup(circuit(&[0; SHA256_MESSAGE.len()]), &mut rng).unwrap();
+        let pvk = prepare_verifying_key(&vk);
+        let proof = Groth16::<Bls12_381>::prove(&pk, circuit(SHA256_MESSAGE), &mut rng).unwrap();
+        let digest = reference::sha256(SHA256_MESSAGE);
+        let verify = |digest: &[u8], proof: &Proof<Bls12_381>| {
+            Groth16::<Bls12_381>::verify_with_processed_vk(&pvk, &digest_inputs(digest), proof).unwrap_or(false)
+        };
+        assert!(verify(&digest, &proof));
+
+        // The honest proof checked against a digest with one bit flipped
+        let mut flipped = digest;
+        flipped[0] ^= 1;
+        assert!(!verify(&flipped, &proof));
+
+        // A proof whose first point is moved by the generator
+        let mut mutated = proof.clone();
+        mutated.a = (mutated.a.into_group() + G1Affine::generator()).into_affine();
+        assert!(!verify(&digest, &mutated));
+
+        // The serialized variants, and a proof of another message of the same length
+        let mut other_message = SHA256_MESSAGE.to_vec();
+        other_message[0] ^= 1;
+        let other = Groth16::<Bls12_381>::prove(&pk, circuit(&other_message), &mut rng).unwrap();
+        let bytes = |proof: &Proof<Bls12_381>| {
+            let mut bytes = Vec::new();
+            proof.serialize_compressed(&mut bytes).unwrap();
+            bytes
+        };
+        tamper::assert_rejects_tampering(&bytes(&proof), &bytes(&other), |bytes| {
+            Proof::<Bls12_381>::deserialize_compressed(bytes).is_ok_and(|proof| verify(&digest, &proof))
+        });
+    }
+}
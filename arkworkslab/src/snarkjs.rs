@@ -0,0 +1,356 @@
+//! Groth16 proofs and verifying keys in the JSON format of snarkjs.
+//!
+//! snarkjs and arkworks implement the same Groth16, and snarkjs' `bn128` is arkworks'
+//! BN254, so a proof made by one verifies with the other once it is translated. snarkjs
+//! keeps a proof in `proof.json`, its public signals in `public.json` and the verifying
+//! key in `verification_key.json`, all as decimal strings:
+//! - a G1 point is `[x, y, "1"]`, and the point at infinity `["0", "1", "0"]`
+//! - a G2 point is `[[x.c0, x.c1], [y.c0, y.c1], ["1", "0"]]`
+//! - `IC` in the key is arkworks' `gamma_abc_g1`, with one point per public signal
+//!   after the first
+//!
+//! The exporters write the files as snarkjs does. The importers refuse points off the
+//! curve or outside the prime-order subgroup and numbers that are not canonical field
+//! elements, so a malformed file fails to load instead of failing to verify. `snarkjs
+//! groth16 verify` does not read `vk_alphabeta_12`, so the exporter leaves it out and
+//! the importer ignores it.
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use ark_bn254::{Bn254, Fq, Fq2, Fr, G1Affine, G2Affine};
+use ark_ec::short_weierstrass::{Affine, SWCurveConfig};
+use ark_ec::AffineRepr;
+use ark_ff::{AdditiveGroup, Field, One, PrimeField, Zero};
+use ark_groth16::{Proof, VerifyingKey};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+const PROTOCOL: &str = "groth16";
+const CURVE: &str = "bn128";
+
+/// `proof.json` as written by `snarkjs groth16 prove`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofJson {
+    pub pi_a: [String; 3],
+    pub pi_b: [[String; 2]; 3],
+    pub pi_c: [String; 3],
+    pub protocol: String,
+    pub curve: String,
+}
+
+/// `verification_key.json` as written by `snarkjs zkey export verificationkey`, without
+/// `vk_alphabeta_12`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerificationKeyJson {
+    pub protocol: String,
+    pub curve: String,
+    #[serde(rename = "nPublic")]
+    pub n_public: usize,
+    pub vk_alpha_1: [String; 3],
+    pub vk_beta_2: [[String; 2]; 3],
+    pub vk_gamma_2: [[String; 2]; 3],
+    pub vk_delta_2: [[String; 2]; 3],
+    #[serde(rename = "IC")]
+    pub ic: Vec<[String; 3]>,
+}
+
+#[derive(Debug)]
+pub enum SnarkjsError {
+    /// The file could not be read or written.
+    Io(std::io::Error),
+    /// The file is not valid JSON or does not have the expected schema.
+    Json(serde_json::Error),
+    /// The file is for another protocol or curve.
+    Unsupported(String),
+    /// A number or point is not valid on BN254.
+    Invalid(String),
+}
+
+impl fmt::Display for SnarkjsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnarkjsError::Io(e) => write!(f, "{}", e),
+            SnarkjsError::Json(e) => write!(f, "{}", e),
+            SnarkjsError::Unsupported(what) => write!(f, "unsupported {}", what),
+            SnarkjsError::Invalid(what) => write!(f, "invalid {}", what),
+        }
+    }
+}
+
+impl std::error::Error for SnarkjsError {}
+
+/// The directory given with `--snarkjs <dir>`, if any.
+pub fn dir_from_args() -> Option<PathBuf> {
+    let args: Vec<String> = std::env::args().collect();
+    args.iter()
+        .position(|a| a == "--snarkjs")
+        .map(|pos| PathBuf::from(args.get(pos + 1).expect("--snarkjs needs a directory")))
+}
+
+/// Translates an arkworks proof to `proof.json`.
+pub fn proof_to_json(proof: &Proof<Bn254>) -> ProofJson {
+    ProofJson {
+        pi_a: g1_to_json(&proof.a),
+        pi_b: g2_to_json(&proof.b),
+        pi_c: g1_to_json(&proof.c),
+        protocol: PROTOCOL.to_string(),
+        curve: CURVE.to_string(),
+    }
+}
+
+/// Translates `proof.json` to an arkworks proof.
+pub fn proof_from_json(json: &ProofJson) -> Result<Proof<Bn254>, SnarkjsError> {
+    check_header(&json.protocol, &json.curve)?;
+    Ok(Proof {
+        a: g1_from_json(&json.pi_a, "pi_a")?,
+        b: g2_from_json(&json.pi_b, "pi_b")?,
+        c: g1_from_json(&json.pi_c, "pi_c")?,
+    })
+}
+
+/// Translates an arkworks verifying key to `verification_key.json`.
+pub fn vk_to_json(vk: &VerifyingKey<Bn254>) -> VerificationKeyJson {
+    VerificationKeyJson {
+        protocol: PROTOCOL.to_string(),
+        curve: CURVE.to_string(),
+        n_public: vk.gamma_abc_g1.len() - 1,
+        vk_alpha_1: g1_to_json(&vk.alpha_g1),
+        vk_beta_2: g2_to_json(&vk.beta_g2),
+        vk_gamma_2: g2_to_json(&vk.gamma_g2),
+        vk_delta_2: g2_to_json(&vk.delta_g2),
+        ic: vk.gamma_abc_g1.iter().map(g1_to_json).collect(),
+    }
+}
+
+/// Translates `verification_key.json` to an arkworks verifying key.
+pub fn vk_from_json(json: &VerificationKeyJson) -> Result<VerifyingKey<Bn254>, SnarkjsError> {
+    check_header(&json.protocol, &json.curve)?;
+    if json.ic.len() != json.n_public + 1 {
+        return Err(SnarkjsError::Invalid(format!(
+            "IC: {} points for {} public signals",
+            json.ic.len(),
+            json.n_public
+        )));
+    }
+    Ok(VerifyingKey {
+        alpha_g1: g1_from_json(&json.vk_alpha_1, "vk_alpha_1")?,
+        beta_g2: g2_from_json(&json.vk_beta_2, "vk_beta_2")?,
+        gamma_g2: g2_from_json(&json.vk_gamma_2, "vk_gamma_2")?,
+        delta_g2: g2_from_json(&json.vk_delta_2, "vk_delta_2")?,
+        gamma_abc_g1: json
+            .ic
+            .iter()
+            .enumerate()
+            .map(|(i, point)| g1_from_json(point, &format!("IC[{}]", i)))
+            .collect::<Result<_, _>>()?,
+    })
+}
+
+/// Translates public inputs to `public.json`.
+pub fn public_to_json(inputs: &[Fr]) -> Vec<String> {
+    inputs.iter().map(|input| input.to_string()).collect()
+}
+
+/// Translates `public.json` to public inputs.
+pub fn public_from_json(json: &[String]) -> Result<Vec<Fr>, SnarkjsError> {
+    json.iter()
+        .enumerate()
+        .map(|(i, signal)| field_from_json(signal, &format!("public signal {}", i)))
+        .collect()
+}
+
+/// Reads a snarkjs JSON file, e.g. `load::<ProofJson>("proof.json")`.
+pub fn load<T: DeserializeOwned>(path: &Path) -> Result<T, SnarkjsError> {
+    let json = fs::read_to_string(path).map_err(SnarkjsError::Io)?;
+    serde_json::from_str(&json).map_err(SnarkjsError::Json)
+}
+
+/// Writes a snarkjs JSON file, indented by one space as snarkjs writes them.
+pub fn save<T: Serialize>(path: &Path, value: &T) -> Result<(), SnarkjsError> {
+    fs::write(path, to_string(value)).map_err(SnarkjsError::Io)
+}
+
+/// `value` as snarkjs formats its JSON files.
+pub fn to_string<T: Serialize>(value: &T) -> String {
+    let mut bytes = Vec::new();
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(b" ");
+    value
+        .serialize(&mut serde_json::Serializer::with_formatter(&mut bytes, formatter))
+        .expect("snarkjs JSON is always serializable");
+    String::from_utf8(bytes).expect("serde_json writes UTF-8")
+}
+
+/// Writes `proof.json`, `public.json` and `verification_key.json` to `dir`.
+pub fn export(dir: &Path, vk: &VerifyingKey<Bn254>, public: &[Fr], proof: &Proof<Bn254>) -> Result<(), SnarkjsError> {
+    fs::create_dir_all(dir).map_err(SnarkjsError::Io)?;
+    save(&dir.join("verification_key.json"), &vk_to_json(vk))?;
+    save(&dir.join("public.json"), &public_to_json(public))?;
+    save(&dir.join("proof.json"), &proof_to_json(proof))
+}
+
+/// The contents of a directory of snarkjs files.
+pub struct Artifacts {
+    pub vk: VerifyingKey<Bn254>,
+    pub public: Vec<Fr>,
+    pub proof: Proof<Bn254>,
+}
+
+/// Reads `proof.json`, `public.json` and `verification_key.json` from `dir`.
+pub fn import(dir: &Path) -> Result<Artifacts, SnarkjsError> {
+    Ok(Artifacts {
+        vk: vk_from_json(&load(&dir.join("verification_key.json"))?)?,
+        public: public_from_json(&load::<Vec<String>>(&dir.join("public.json"))?)?,
+        proof: proof_from_json(&load(&dir.join("proof.json"))?)?,
+    })
+}
+
+fn check_header(protocol: &str, curve: &str) -> Result<(), SnarkjsError> {
+    if protocol != PROTOCOL {
+        return Err(SnarkjsError::Unsupported(format!("protocol {:?}", protocol)));
+    }
+    if curve != CURVE {
+        return Err(SnarkjsError::Unsupported(format!("curve {:?}", curve)));
+    }
+    Ok(())
+}
+
+fn g1_to_json(point: &G1Affine) -> [String; 3] {
+    match point.xy() {
+        Some((x, y)) => [x.to_string(), y.to_string(), "1".to_string()],
+        None => ["0".to_string(), "1".to_string(), "0".to_string()],
+    }
+}
+
+fn g2_to_json(point: &G2Affine) -> [[String; 2]; 3] {
+    let fq2 = |c: Fq2| [c.c0.to_string(), c.c1.to_string()];
+    match point.xy() {
+        Some((x, y)) => [fq2(x), fq2(y), fq2(Fq2::ONE)],
+        None => [fq2(Fq2::ZERO), fq2(Fq2::ONE), fq2(Fq2::ZERO)],
+    }
+}
+
+fn g1_from_json(json: &[String; 3], name: &str) -> Result<G1Affine, SnarkjsError> {
+    let [x, y, z] = json.each_ref().map(|c| field_from_json::<Fq>(c, name));
+    let (x, y, z) = (x?, y?, z?);
+    if z.is_zero() {
+        return Ok(G1Affine::zero());
+    }
+    if !z.is_one() {
+        return Err(SnarkjsError::Invalid(format!("{}: not in affine coordinates", name)));
+    }
+    checked(G1Affine::new_unchecked(x, y), name)
+}
+
+fn g2_from_json(json: &[[String; 2]; 3], name: &str) -> Result<G2Affine, SnarkjsError> {
+    let fq2 = |[c0, c1]: &[String; 2]| -> Result<Fq2, SnarkjsError> {
+        Ok(Fq2::new(field_from_json(c0, name)?, field_from_json(c1, name)?))
+    };
+    let (x, y, z) = (fq2(&json[0])?, fq2(&json[1])?, fq2(&json[2])?);
+    if z.is_zero() {
+        return Ok(G2Affine::zero());
+    }
+    if !z.is_one() {
+        return Err(SnarkjsError::Invalid(format!("{}: not in affine coordinates", name)));
+    }
+    checked(G2Affine::new_unchecked(x, y), name)
+}
+
+pub(crate) fn checked<P: SWCurveConfig>(point: Affine<P>, name: &str) -> Result<Affine<P>, SnarkjsError> {
+    // `new_unchecked` does neither check, and a point outside the subgroup breaks the
+    // soundness of the pairing check
+    if !point.is_on_curve() || !point.is_in_correct_subgroup_assuming_on_curve() {
+        return Err(SnarkjsError::Invalid(format!("{}: not a point of the group", name)));
+    }
+    Ok(point)
+}
+
+fn field_from_json<F: PrimeField>(json: &str, name: &str) -> Result<F, SnarkjsError> {
+    // `from_str` reduces modulo p and accepts signs, so only take what it prints back
+    F::from_str(json)
+        .ok()
+        .filter(|f| f.to_string() == json)
+        .ok_or_else(|| SnarkjsError::Invalid(format!("{}: {:?} is not a canonical field element", name, json)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_crypto_primitives::snark::SNARK;
+    use ark_groth16::Groth16;
+
+    /// Written by `cubic_expression --seed 0 --input ../labkit/inputs/cubic.json --snarkjs <dir>`
+    fn fixture() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures").join("cubic_groth16")
+    }
+
+    #[test]
+    fn test_fixture_round_trips() {
+        let Artifacts { vk, public, proof } = import(&fixture()).unwrap();
+        assert!(Groth16::<Bn254>::verify(&vk, &public, &proof).unwrap());
+        assert_eq!(public, [Fr::from(35u64)]);
+
+        // Exporting what was imported gives back the files byte for byte
+        let file = |name: &str| fs::read_to_string(fixture().join(name)).unwrap();
+        assert_eq!(to_string(&vk_to_json(&vk)), file("verification_key.json"));
+        assert_eq!(to_string(&public_to_json(&public)), file("public.json"));
+        assert_eq!(to_string(&proof_to_json(&proof)), file("proof.json"));
+    }
+
+    // The fixture above comes from arkworks, so the round trip only shows that the
+    // exporter and the importer agree. This reads what `snarkjs groth16 prove` and
+    // `snarkjs zkey export verificationkey` write in `snarkjs/cubic_groth16`, which is
+    // not committed as snarkjs is not available where the fixtures are built; run its
+    // `start.sh` first.
+    #[test]
+    #[ignore = "needs the files snarkjs/cubic_groth16/start.sh writes"]
+    fn test_snarkjs_proof_verifies() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../snarkjs/cubic_groth16");
+        let Artifacts { vk, public, proof } = import(&dir).unwrap();
+        assert_eq!(public, [Fr::from(35u64)]);
+        assert!(Groth16::<Bn254>::verify(&vk, &public, &proof).unwrap());
+        assert!(!Groth16::<Bn254>::verify(&vk, &[Fr::from(36u64)], &proof).unwrap());
+    }
+
+    #[test]
+    fn test_points_at_infinity_round_trip() {
+        let json = g1_to_json(&G1Affine::zero());
+        assert_eq!(json, ["0", "1", "0"]);
+        assert_eq!(g1_from_json(&json, "g1").unwrap(), G1Affine::zero());
+        let json = g2_to_json(&G2Affine::zero());
+        assert_eq!(g2_from_json(&json, "g2").unwrap(), G2Affine::zero());
+        let generator = G2Affine::generator();
+        assert_eq!(g2_from_json(&g2_to_json(&generator), "g2").unwrap(), generator);
+    }
+
+    #[test]
+    fn test_malformed_files_are_refused() {
+        let proof: ProofJson = load(&fixture().join("proof.json")).unwrap();
+        let vk: VerificationKeyJson = load(&fixture().join("verification_key.json")).unwrap();
+        let modulus = Fq::MODULUS.to_string();
+
+        let mut proofs = Vec::new();
+        let mut tamper = |f: &dyn Fn(&mut ProofJson)| {
+            let mut json = proof.clone();
+            f(&mut json);
+            proofs.push(json);
+        };
+        tamper(&|json| json.protocol = "plonk".to_string());
+        tamper(&|json| json.curve = "bls12381".to_string());
+        tamper(&|json| json.pi_a[2] = "2".to_string());
+        tamper(&|json| json.pi_a[0] = "1".to_string());
+        tamper(&|json| json.pi_c[1] = format!("-{}", json.pi_c[1]));
+        tamper(&|json| json.pi_c[1] = format!("0{}", json.pi_c[1]));
+        tamper(&|json| json.pi_b[0][1] = modulus.clone());
+        tamper(&|json| json.pi_b[1][0] = "1".to_string());
+        for json in proofs {
+            assert!(proof_from_json(&json).is_err(), "{} should be refused", to_string(&json));
+        }
+
+        let mut short = vk.clone();
+        short.ic.pop();
+        assert!(vk_from_json(&short).is_err());
+        assert!(public_from_json(&[Fr::MODULUS.to_string()]).is_err());
+        assert!(public_from_json(&["35".to_string()]).is_ok());
+    }
+}
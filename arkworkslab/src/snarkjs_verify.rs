@@ -0,0 +1,23 @@
+use arklab::snarkjs;
+use ark_bn254::Bn254;
+use ark_crypto_primitives::snark::SNARK;
+use ark_groth16::Groth16;
+use std::path::PathBuf;
+
+/// Verifies a snarkjs Groth16 proof with arkworks. The directory holds the
+/// `verification_key.json`, `public.json` and `proof.json` written by snarkjs, or by
+/// `cubic_expression --snarkjs <dir>`.
+pub fn main() {
+    let Some(dir) = std::env::args().nth(1).map(PathBuf::from) else {
+        eprintln!("usage: snarkjs_verify <dir>");
+        std::process::exit(1);
+    };
+    let snarkjs::Artifacts { vk, public, proof } =
+        snarkjs::import(&dir).unwrap_or_else(|e| panic!("cannot load snarkjs files from {}: {}", dir.display(), e));
+    println!("Public signals: {}", snarkjs::public_to_json(&public).join(", "));
+    let is_valid = Groth16::<Bn254>::verify(&vk, &public, &proof).expect("the public signals do not match the key");
+    println!("Proof is valid: {}", is_valid);
+    if !is_valid {
+        std::process::exit(1);
+    }
+}
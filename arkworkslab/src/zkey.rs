@@ -0,0 +1,547 @@
+//! snarkjs Groth16 proving keys (`.zkey`) and witnesses (`.wtns`).
+//!
+//! The snarkjs `*_groth16` examples end their ceremony with a `circuit_final.zkey` and
+//! compute a `witness.wtns` from `input.json`. Both are binary files of numbered
+//! sections. `read_zkey` turns the key into an arkworks `ProvingKey<Bn254>` together
+//! with the A and B matrices of the circuit, and `read_wtns` turns the witness into
+//! the full assignment, so `prove` can make a proof with arkworks for a key from an
+//! existing ceremony. The proof verifies against the same `verification_key.json` as
+//! the snarkjs one (see `crate::snarkjs`).
+//!
+//! The zkey sections of a Groth16 key are:
+//! 1. the protocol, 1 for Groth16
+//! 2. the field moduli, the numbers of signals and public signals, the domain size and
+//!    the points of the verifying key plus `beta_g1` and `delta_g1`
+//! 3. `IC`, i.e. `gamma_abc_g1`
+//! 4. the non-zero coefficients of A and B as (matrix, constraint, signal, value)
+//! 5. to 9. `a_query`, `b_g1_query`, `b_g2_query`, `l_query` and `h_query`
+//!
+//! Points are affine and the point at infinity is all zeros. Coordinates are little
+//! endian in Montgomery form, and the coefficients of section 4 carry one more factor of
+//! the Montgomery constant. Witness values are plain little-endian integers.
+//!
+//! snarkjs reduces R1CS to a QAP differently from arkworks' default: the prover
+//! evaluates the quotient on the odd coset of the domain, and `h_query` holds the
+//! matching Lagrange basis. `CircomReduction` is that reduction, and keys for it can
+//! also be generated by arkworks with `Groth16::<Bn254, CircomReduction>`.
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use ark_bn254::{Bn254, Fq, Fq2, Fr, G1Affine, G2Affine};
+use ark_ff::{BigInteger256, PrimeField, UniformRand, Zero};
+use ark_groth16::r1cs_to_qap::{evaluate_constraint, LibsnarkReduction, R1CSToQAP};
+use ark_groth16::{Groth16, Proof, ProvingKey, VerifyingKey};
+use ark_poly::EvaluationDomain;
+use ark_relations::r1cs::{ConstraintMatrices, ConstraintSystemRef, SynthesisError};
+use ark_serialize::CanonicalDeserialize;
+use ark_std::rand::{CryptoRng, RngCore};
+
+use crate::snarkjs::{checked, SnarkjsError};
+
+const GROTH16: u32 = 1;
+
+/// A snarkjs Groth16 proving key.
+pub struct Zkey {
+    pub pk: ProvingKey<Bn254>,
+    /// The A and B matrices of the circuit. snarkjs does not store C, which the prover
+    /// does not need.
+    pub matrices: ConstraintMatrices<Fr>,
+}
+
+impl Zkey {
+    /// The number of public signals, outputs first, then public inputs.
+    pub fn num_public(&self) -> usize {
+        self.matrices.num_instance_variables - 1
+    }
+
+    /// The number of signals, including the constant one.
+    pub fn num_signals(&self) -> usize {
+        self.matrices.num_instance_variables + self.matrices.num_witness_variables
+    }
+}
+
+/// Reads the `.zkey` file at `path`.
+pub fn load_zkey(path: &Path) -> Result<Zkey, SnarkjsError> {
+    read_zkey(&fs::read(path).map_err(SnarkjsError::Io)?)
+}
+
+/// Reads the `.wtns` file at `path`.
+pub fn load_wtns(path: &Path) -> Result<Vec<Fr>, SnarkjsError> {
+    read_wtns(&fs::read(path).map_err(SnarkjsError::Io)?)
+}
+
+/// Parses a snarkjs Groth16 proving key.
+pub fn read_zkey(bytes: &[u8]) -> Result<Zkey, SnarkjsError> {
+    let sections = sections(bytes, b"zkey")?;
+    let protocol = Reader::new(section(&sections, 1)?).u32()?;
+    if protocol != GROTH16 {
+        let name = match protocol {
+            2 => "PLONK".to_string(),
+            10 => "FFLONK".to_string(),
+            id => format!("protocol {}", id),
+        };
+        return Err(SnarkjsError::Unsupported(format!("zkey for {}, not Groth16", name)));
+    }
+
+    let mut header = Reader::new(section(&sections, 2)?);
+    header.modulus::<Fq>("base field")?;
+    header.modulus::<Fr>("scalar field")?;
+    let num_signals = header.u32()? as usize;
+    let num_public = header.u32()? as usize;
+    let domain_size = header.u32()? as usize;
+    if num_public >= num_signals || !domain_size.is_power_of_two() {
+        return Err(SnarkjsError::Invalid("zkey header".to_string()));
+    }
+    let alpha_g1 = header.g1()?;
+    let beta_g1 = header.g1()?;
+    let beta_g2 = header.g2()?;
+    let gamma_g2 = header.g2()?;
+    let delta_g1 = header.g1()?;
+    let delta_g2 = header.g2()?;
+
+    let g1s = |id, n| Reader::new(section(&sections, id)?).points(n, Reader::g1);
+    let vk = VerifyingKey {
+        alpha_g1,
+        beta_g2,
+        gamma_g2,
+        delta_g2,
+        gamma_abc_g1: g1s(3, num_public + 1)?,
+    };
+    let pk = ProvingKey {
+        vk,
+        beta_g1,
+        delta_g1,
+        a_query: g1s(5, num_signals)?,
+        b_g1_query: g1s(6, num_signals)?,
+        b_g2_query: Reader::new(section(&sections, 7)?).points(num_signals, Reader::g2)?,
+        l_query: g1s(8, num_signals - num_public - 1)?,
+        h_query: g1s(9, domain_size)?,
+    };
+    let matrices = read_coefficients(section(&sections, 4)?, num_signals, num_public, domain_size)?;
+    Ok(Zkey { pk, matrices })
+}
+
+/// Parses a snarkjs witness into the full assignment: the constant one, the public
+/// signals, then the private ones.
+pub fn read_wtns(bytes: &[u8]) -> Result<Vec<Fr>, SnarkjsError> {
+    let sections = sections(bytes, b"wtns")?;
+    let mut header = Reader::new(section(&sections, 1)?);
+    header.modulus::<Fr>("witness field")?;
+    let len = header.u32()? as usize;
+
+    let mut values = Reader::new(section(&sections, 2)?);
+    (0..len)
+        .map(|i| {
+            Fr::from_bigint(values.bigint()?)
+                .ok_or_else(|| SnarkjsError::Invalid(format!("witness value {}: not a field element", i)))
+        })
+        .collect()
+}
+
+/// Proves with a snarkjs key. `witness` is the full assignment from `read_wtns`.
+pub fn prove<R: RngCore + CryptoRng>(zkey: &Zkey, witness: &[Fr], rng: &mut R) -> Result<Proof<Bn254>, SynthesisError> {
+    if witness.len() != zkey.num_signals() {
+        return Err(SynthesisError::AssignmentMissing);
+    }
+    let (r, s) = (Fr::rand(rng), Fr::rand(rng));
+    Groth16::<Bn254, CircomReduction>::create_proof_with_reduction_and_matrices(
+        &zkey.pk,
+        r,
+        s,
+        &zkey.matrices,
+        zkey.matrices.num_instance_variables,
+        zkey.matrices.num_constraints,
+        witness,
+    )
+}
+
+/// The R1CS-to-QAP reduction of snarkjs, as described in the module documentation.
+pub struct CircomReduction;
+
+impl R1CSToQAP for CircomReduction {
+    #[allow(clippy::type_complexity)]
+    fn instance_map_with_evaluation<F: PrimeField, D: EvaluationDomain<F>>(
+        cs: ConstraintSystemRef<F>,
+        t: &F,
+    ) -> Result<(Vec<F>, Vec<F>, Vec<F>, F, usize, usize), SynthesisError> {
+        LibsnarkReduction::instance_map_with_evaluation::<F, D>(cs, t)
+    }
+
+    fn witness_map_from_matrices<F: PrimeField, D: EvaluationDomain<F>>(
+        matrices: &ConstraintMatrices<F>,
+        num_inputs: usize,
+        num_constraints: usize,
+        full_assignment: &[F],
+    ) -> Result<Vec<F>, SynthesisError> {
+        let domain = D::new(num_constraints + num_inputs).ok_or(SynthesisError::PolynomialDegreeTooLarge)?;
+        let domain_size = domain.size();
+
+        // A and B on the domain, with the copy of each public signal that both
+        // reductions append to A. C = A * B on the constraints.
+        let mut a = vec![F::zero(); domain_size];
+        let mut b = vec![F::zero(); domain_size];
+        for (i, (a_i, b_i)) in matrices.a.iter().zip(&matrices.b).take(num_constraints).enumerate() {
+            a[i] = evaluate_constraint(a_i, full_assignment);
+            b[i] = evaluate_constraint(b_i, full_assignment);
+        }
+        a[num_constraints..num_constraints + num_inputs].copy_from_slice(&full_assignment[..num_inputs]);
+        let mut c: Vec<F> = a.iter().zip(&b).take(num_constraints).map(|(a, b)| *a * b).collect();
+        c.resize(domain_size, F::zero());
+
+        // Move to the odd coset, i.e. multiply the i-th coefficient by g^i for g a
+        // primitive root of unity of twice the domain size. The vanishing polynomial is
+        // -2 everywhere on it, which `h_query` already accounts for.
+        let g = D::new(2 * domain_size)
+            .ok_or(SynthesisError::PolynomialDegreeTooLarge)?
+            .element(1);
+        for evals in [&mut a, &mut b, &mut c] {
+            domain.ifft_in_place(evals);
+            D::distribute_powers_and_mul_by_const(evals, g, F::one());
+            domain.fft_in_place(evals);
+        }
+        Ok(a.iter().zip(&b).zip(&c).map(|((a, b), c)| *a * b - c).collect())
+    }
+
+    fn h_query_scalars<F: PrimeField, D: EvaluationDomain<F>>(
+        max_power: usize,
+        t: F,
+        _: F,
+        delta_inverse: F,
+    ) -> Result<Vec<F>, SynthesisError> {
+        // The Lagrange basis of the odd coset at t, over delta: the odd coefficients of
+        // the powers of t on the domain of twice the size
+        let mut scalars: Vec<F> = (0..2 * max_power + 1)
+            .map(|i| delta_inverse * t.pow([i as u64]))
+            .collect();
+        let domain = D::new(scalars.len()).ok_or(SynthesisError::PolynomialDegreeTooLarge)?;
+        domain.ifft_in_place(&mut scalars);
+        Ok(scalars.into_iter().skip(1).step_by(2).collect())
+    }
+}
+
+/// The A and B matrices from section 4. Past the constraints of the circuit snarkjs
+/// lists the public signal copies in A, which `CircomReduction` adds itself.
+fn read_coefficients(
+    bytes: &[u8],
+    num_signals: usize,
+    num_public: usize,
+    domain_size: usize,
+) -> Result<ConstraintMatrices<Fr>, SnarkjsError> {
+    let mut reader = Reader::new(bytes);
+    let len = reader.u32()?;
+    let mut matrices = [vec![Vec::new(); domain_size], vec![Vec::new(); domain_size]];
+    let mut rows = 0;
+    for _ in 0..len {
+        let (matrix, constraint, signal) = (reader.u32()? as usize, reader.u32()? as usize, reader.u32()? as usize);
+        // Stored as value * R^2, so leave Montgomery form twice
+        let value = Fr::new_unchecked(Fr::new_unchecked(reader.montgomery(Fr::MODULUS)?).into_bigint());
+        if matrix > 1 || constraint >= domain_size || signal >= num_signals {
+            return Err(SnarkjsError::Invalid(format!(
+                "coefficient of signal {} in constraint {} of matrix {}",
+                signal, constraint, matrix
+            )));
+        }
+        matrices[matrix][constraint].push((value, signal));
+        rows = rows.max(constraint + 1);
+    }
+
+    let num_constraints = rows
+        .checked_sub(num_public + 1)
+        .ok_or_else(|| SnarkjsError::Invalid("zkey without public signal constraints".to_string()))?;
+    let [mut a, mut b] = matrices;
+    a.truncate(num_constraints);
+    b.truncate(num_constraints);
+    Ok(ConstraintMatrices {
+        num_instance_variables: num_public + 1,
+        num_witness_variables: num_signals - num_public - 1,
+        num_constraints,
+        a_num_non_zero: a.iter().map(Vec::len).sum(),
+        b_num_non_zero: b.iter().map(Vec::len).sum(),
+        c_num_non_zero: 0,
+        a,
+        b,
+        c: Vec::new(),
+    })
+}
+
+/// The sections of a snarkjs binary file by id: after the four-byte `magic`, a version,
+/// the number of sections and, for every section, its id, its length and its bytes.
+fn sections<'a>(bytes: &'a [u8], magic: &[u8; 4]) -> Result<HashMap<u32, &'a [u8]>, SnarkjsError> {
+    let mut reader = Reader::new(bytes);
+    if reader.take(4)? != magic {
+        return Err(SnarkjsError::Unsupported(format!("file type, expected {}", String::from_utf8_lossy(magic))));
+    }
+    let _version = reader.u32()?;
+    let mut sections = HashMap::new();
+    for _ in 0..reader.u32()? {
+        let id = reader.u32()?;
+        let len = usize::try_from(reader.u64()?).map_err(|_| SnarkjsError::Invalid(format!("section {}", id)))?;
+        sections.entry(id).or_insert(reader.take(len)?);
+    }
+    Ok(sections)
+}
+
+fn section<'a>(sections: &HashMap<u32, &'a [u8]>, id: u32) -> Result<&'a [u8], SnarkjsError> {
+    sections
+        .get(&id)
+        .copied()
+        .ok_or_else(|| SnarkjsError::Invalid(format!("missing section {}", id)))
+}
+
+/// Reads little-endian values from a section.
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Reader { bytes }
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'a [u8], SnarkjsError> {
+        if self.bytes.len() < n {
+            return Err(SnarkjsError::Invalid("truncated file".to_string()));
+        }
+        let (head, tail) = self.bytes.split_at(n);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    fn u32(&mut self) -> Result<u32, SnarkjsError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, SnarkjsError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn bigint(&mut self) -> Result<BigInteger256, SnarkjsError> {
+        BigInteger256::deserialize_uncompressed(self.take(32)?).map_err(|e| SnarkjsError::Invalid(e.to_string()))
+    }
+
+    /// Checks a field given as its size in bytes followed by its modulus.
+    fn modulus<F: PrimeField<BigInt = BigInteger256>>(&mut self, name: &str) -> Result<(), SnarkjsError> {
+        if self.u32()? != 32 || self.bigint()? != F::MODULUS {
+            return Err(SnarkjsError::Unsupported(format!("{}, expected that of BN254", name)));
+        }
+        Ok(())
+    }
+
+    /// An element of the field of `modulus` in Montgomery form, as its raw limbs.
+    fn montgomery(&mut self, modulus: BigInteger256) -> Result<BigInteger256, SnarkjsError> {
+        let bigint = self.bigint()?;
+        if bigint >= modulus {
+            return Err(SnarkjsError::Invalid("field element out of range".to_string()));
+        }
+        Ok(bigint)
+    }
+
+    fn fq(&mut self) -> Result<Fq, SnarkjsError> {
+        // `new_unchecked` takes the Montgomery form as it is
+        Ok(Fq::new_unchecked(self.montgomery(Fq::MODULUS)?))
+    }
+
+    fn g1(&mut self) -> Result<G1Affine, SnarkjsError> {
+        let (x, y) = (self.fq()?, self.fq()?);
+        if x.is_zero() && y.is_zero() {
+            return Ok(G1Affine::identity());
+        }
+        checked(G1Affine::new_unchecked(x, y), "zkey G1 point")
+    }
+
+    fn g2(&mut self) -> Result<G2Affine, SnarkjsError> {
+        let mut fq2 = || -> Result<Fq2, SnarkjsError> { Ok(Fq2::new(self.fq()?, self.fq()?)) };
+        let (x, y) = (fq2()?, fq2()?);
+        if x.is_zero() && y.is_zero() {
+            return Ok(G2Affine::identity());
+        }
+        checked(G2Affine::new_unchecked(x, y), "zkey G2 point")
+    }
+
+    fn points<P>(&mut self, n: usize, read: fn(&mut Self) -> Result<P, SnarkjsError>) -> Result<Vec<P>, SnarkjsError> {
+        (0..n).map(|_| read(self)).collect()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snarkjs;
+    use labkit::rng::ExampleRng;
+    use std::path::PathBuf;
+    use ark_crypto_primitives::snark::SNARK;
+    use ark_ec::AffineRepr;
+    use ark_relations::lc;
+    use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem, SynthesisMode, Variable};
+    use ark_serialize::CanonicalSerialize;
+
+    /// Copied from `snarkjs/cubic_fflonk`: the witness circom computes for `input.json`,
+    /// x = 3, and the FFLONK key, which only serves to check that other keys than
+    /// Groth16 ones are refused
+    fn fixture(name: &str) -> Vec<u8> {
+        fs::read(Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures").join("circom_cubic").join(name)).unwrap()
+    }
+
+    /// The circuit circom compiles `cubic_expression.circom` to. Its signals are the
+    /// constant one, out, x and xx, as in `witness.wtns`.
+    struct CircomCubic;
+
+    impl ConstraintSynthesizer<Fr> for CircomCubic {
+        fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+            let out = cs.new_input_variable(|| Err(SynthesisError::AssignmentMissing))?;
+            let x = cs.new_witness_variable(|| Err(SynthesisError::AssignmentMissing))?;
+            let xx = cs.new_witness_variable(|| Err(SynthesisError::AssignmentMissing))?;
+            cs.enforce_constraint(lc!() + x, lc!() + x, lc!() + xx)?;
+            cs.enforce_constraint(lc!() + xx, lc!() + x, lc!() + out - x - (Fr::from(5u64), Variable::One))
+        }
+    }
+
+    /// Writes `pk` in the layout of snarkjs, the inverse of `read_zkey`.
+    fn write_zkey(pk: &ProvingKey<Bn254>, matrices: &ConstraintMatrices<Fr>) -> Vec<u8> {
+        let fq = |out: &mut Vec<u8>, f: Fq| f.0.serialize_uncompressed(out).unwrap();
+        let g1 = |out: &mut Vec<u8>, p: &G1Affine| {
+            let (x, y) = p.xy().unwrap_or_default();
+            fq(out, x);
+            fq(out, y);
+        };
+        let g2 = |out: &mut Vec<u8>, p: &G2Affine| {
+            let (x, y) = p.xy().unwrap_or_default();
+            for c in [x.c0, x.c1, y.c0, y.c1] {
+                fq(out, c);
+            }
+        };
+        let g1s = |points: &[G1Affine]| {
+            let mut out = Vec::new();
+            points.iter().for_each(|p| g1(&mut out, p));
+            out
+        };
+        let num_public = matrices.num_instance_variables - 1;
+
+        let mut header = Vec::new();
+        for modulus in [Fq::MODULUS, Fr::MODULUS] {
+            header.extend(32u32.to_le_bytes());
+            modulus.serialize_uncompressed(&mut header).unwrap();
+        }
+        let num_signals = matrices.num_instance_variables + matrices.num_witness_variables;
+        for n in [num_signals, num_public, pk.h_query.len()] {
+            header.extend((n as u32).to_le_bytes());
+        }
+        g1(&mut header, &pk.vk.alpha_g1);
+        g1(&mut header, &pk.beta_g1);
+        g2(&mut header, &pk.vk.beta_g2);
+        g2(&mut header, &pk.vk.gamma_g2);
+        g1(&mut header, &pk.delta_g1);
+        g2(&mut header, &pk.vk.delta_g2);
+
+        let public_rows = (0..=num_public).map(|s| (0, matrices.num_constraints + s, vec![(Fr::from(1u64), s)]));
+        let rows = (matrices.a.iter().map(|row| (0, row)).enumerate())
+            .chain(matrices.b.iter().map(|row| (1, row)).enumerate())
+            .map(|(i, (matrix, row))| (matrix, i, row.clone()))
+            .chain(public_rows);
+        let mut coefficients = Vec::new();
+        let mut len = 0u32;
+        for (matrix, constraint, row) in rows {
+            for (value, signal) in row {
+                for n in [matrix, constraint, signal] {
+                    coefficients.extend((n as u32).to_le_bytes());
+                }
+                // value * R^2: the Montgomery form of the Montgomery form
+                Fr::from_bigint(value.0).unwrap().0.serialize_uncompressed(&mut coefficients).unwrap();
+                len += 1;
+            }
+        }
+        coefficients.splice(0..0, len.to_le_bytes());
+
+        let mut b_g2 = Vec::new();
+        pk.b_g2_query.iter().for_each(|p| g2(&mut b_g2, p));
+        let sections = [
+            GROTH16.to_le_bytes().to_vec(),
+            header,
+            g1s(&pk.vk.gamma_abc_g1),
+            coefficients,
+            g1s(&pk.a_query),
+            g1s(&pk.b_g1_query),
+            b_g2,
+            g1s(&pk.l_query),
+            g1s(&pk.h_query),
+        ];
+        let mut bytes = b"zkey".to_vec();
+        bytes.extend(1u32.to_le_bytes());
+        bytes.extend((sections.len() as u32).to_le_bytes());
+        for (id, section) in sections.iter().enumerate() {
+            bytes.extend((id as u32 + 1).to_le_bytes());
+            bytes.extend((section.len() as u64).to_le_bytes());
+            bytes.extend(section);
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_snarkjs_witness_is_read() {
+        let witness = read_wtns(&fixture("witness.wtns")).unwrap();
+        assert_eq!(witness, [1u64, 35, 3, 9].map(Fr::from));
+    }
+
+    // `write_zkey` is the test's own inverse of `read_zkey`, so this only checks that
+    // the two agree on the layout. It is no evidence that keys written by snarkjs read
+    // correctly; that needs a Groth16 key from `snarkjs groth16 setup` among the fixtures.
+    #[test]
+    fn test_written_zkey_is_read_back() {
+        let mut rng = ExampleRng::new(Some(0));
+        let pk = Groth16::<Bn254, CircomReduction>::generate_random_parameters_with_reduction(CircomCubic, &mut rng)
+            .unwrap();
+        let cs = ConstraintSystem::new_ref();
+        cs.set_mode(SynthesisMode::Setup);
+        CircomCubic.generate_constraints(cs.clone()).unwrap();
+        cs.finalize();
+        let matrices = cs.to_matrices().unwrap();
+
+        let zkey = read_zkey(&write_zkey(&pk, &matrices)).unwrap();
+        assert!(zkey.pk == pk);
+        assert_eq!((zkey.matrices.a.clone(), zkey.matrices.b.clone()), (matrices.a, matrices.b));
+        assert_eq!((zkey.num_public(), zkey.num_signals()), (1, 4));
+
+        // The witness circom computed for x = 3 proves out = 35
+        let witness = read_wtns(&fixture("witness.wtns")).unwrap();
+        let proof = prove(&zkey, &witness, &mut rng).unwrap();
+        assert!(Groth16::<Bn254>::verify(&zkey.pk.vk, &[Fr::from(35u64)], &proof).unwrap());
+        assert!(!Groth16::<Bn254>::verify(&zkey.pk.vk, &[Fr::from(36u64)], &proof).unwrap());
+        assert!(prove(&zkey, &witness[..3], &mut rng).is_err());
+    }
+
+    /// `snarkjs/cubic_groth16` after its `start.sh` has run the ceremony for the same
+    /// circuit and computed the witness for x = 3
+    fn snarkjs_groth16(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("../snarkjs/cubic_groth16").join(name)
+    }
+
+    // The key, witness and verifying key are written by snarkjs itself, so this is the
+    // test that a real ceremony key reads correctly. They are not committed, as snarkjs
+    // is not available where the fixtures are built; run `start.sh` first.
+    #[test]
+    #[ignore = "needs the files snarkjs/cubic_groth16/start.sh writes"]
+    fn test_snarkjs_groth16_key_proves() {
+        let zkey = load_zkey(&snarkjs_groth16("circuit_final.zkey")).unwrap();
+        let witness = load_wtns(&snarkjs_groth16("witness.wtns")).unwrap();
+        let vk = snarkjs::vk_from_json(&snarkjs::load(&snarkjs_groth16("verification_key.json")).unwrap()).unwrap();
+        assert!(zkey.pk.vk == vk, "the key does not match snarkjs' verification_key.json");
+
+        let proof = prove(&zkey, &witness, &mut ExampleRng::new(None)).unwrap();
+        assert!(Groth16::<Bn254>::verify(&vk, &[Fr::from(35u64)], &proof).unwrap());
+        assert!(!Groth16::<Bn254>::verify(&vk, &[Fr::from(36u64)], &proof).unwrap());
+    }
+
+    #[test]
+    fn test_malformed_zkeys_are_refused() {
+        let bytes = fixture("fflonk.zkey");
+        assert!(matches!(read_zkey(&bytes), Err(SnarkjsError::Unsupported(_))));
+        assert!(matches!(read_wtns(&bytes), Err(SnarkjsError::Unsupported(_))));
+        for len in [0, 4, 12, 100, bytes.len() / 2] {
+            assert!(read_zkey(&bytes[..len]).is_err(), "zkey truncated to {} bytes was read", len);
+        }
+        let witness = fixture("witness.wtns");
+        assert!(read_wtns(&witness[..witness.len() - 1]).is_err());
+    }
+}
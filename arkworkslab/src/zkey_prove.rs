@@ -0,0 +1,82 @@
+use labkit::bench::{self, PhaseTimes};
+use labkit::memory;
+use labkit::rng::ExampleRng;
+use arklab::snarkjs;
+use arklab::zkey::{self, Zkey};
+use ark_bn254::{Bn254, Fr};
+use ark_groth16::{prepare_verifying_key, Groth16, Proof};
+use ark_serialize::CanonicalSerialize;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+// Counts the heap usage that `memory::measure` reports
+#[global_allocator]
+static ALLOCATOR: labkit::memory::PeakAllocator = labkit::memory::PeakAllocator;
+
+/// Proves with a proving key and a witness made by snarkjs, e.g. the
+/// `circuit_final.zkey` and `witness.wtns` of a `*_groth16` example, so the arkworks
+/// prover can be timed against `snarkjs groth16 prove` on the same key.
+pub fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let (Some(zkey_path), Some(wtns_path)) = (args.get(1), args.get(2)) else {
+        eprintln!("usage: zkey_prove <circuit.zkey> <witness.wtns> [--bench [samples]] [--snarkjs <dir>]");
+        std::process::exit(1);
+    };
+
+    let start = Instant::now();
+    let zkey = zkey::load_zkey(Path::new(zkey_path)).unwrap_or_else(|e| panic!("cannot load {}: {}", zkey_path, e));
+    let witness = zkey::load_wtns(Path::new(wtns_path)).unwrap_or_else(|e| panic!("cannot load {}: {}", wtns_path, e));
+    println!("Load time: {:.3} milliseconds", start.elapsed().as_secs_f64() * 1000.0);
+    println!(
+        "Signals: {}, public signals: {}, constraints: {}",
+        zkey.num_signals(),
+        zkey.num_public(),
+        zkey.matrices.num_constraints
+    );
+
+    if let Some(config) = bench::BenchConfig::from_args() {
+        bench::run("zkey_prove", &config, || prove_and_verify(&zkey, &witness).0);
+        return;
+    }
+    let (_, proof) = prove_and_verify(&zkey, &witness);
+
+    // `--snarkjs <dir>`: write the proof for `snarkjs groth16 verify`
+    if let Some(dir) = snarkjs::dir_from_args() {
+        snarkjs::export(&dir, &zkey.pk.vk, public_signals(&zkey, &witness), &proof).expect("cannot write snarkjs files");
+        println!("Wrote verification_key.json, public.json and proof.json to {}", dir.display());
+    }
+}
+
+/// The public signals follow the constant one at the start of the witness
+fn public_signals<'a>(zkey: &Zkey, witness: &'a [Fr]) -> &'a [Fr] {
+    &witness[1..=zkey.num_public()]
+}
+
+/// Proves and verifies once, returns the time of each phase. The key comes from the
+/// ceremony, so there is no setup.
+fn prove_and_verify(zkey: &Zkey, witness: &[Fr]) -> (PhaseTimes, Proof<Bn254>) {
+    // OS randomness, or a reproducible stream with `--seed <n>`
+    let mut rng = ExampleRng::from_args();
+    let pvk = prepare_verifying_key(&zkey.pk.vk);
+
+    let start1 = Instant::now();
+    let (proof, _) = memory::measure("Prove", || {
+        zkey::prove(zkey, witness, &mut rng).expect("the witness does not fit the key")
+    });
+    let start2 = Instant::now();
+    println!("Uncompressed proof size: {} bytes", proof.uncompressed_size());
+    let (is_valid, _) = memory::measure("Verify", || {
+        Groth16::<Bn254>::verify_proof(&pvk, &proof, public_signals(zkey, witness)).unwrap()
+    });
+    let start3 = Instant::now();
+    println!("Proof is valid: {}", is_valid);
+
+    println!("Prove time: {:.3} milliseconds", start2.duration_since(start1).as_secs_f64() * 1000.0);
+    println!("Verify time: {:.3} milliseconds", start3.duration_since(start2).as_secs_f64() * 1000.0);
+    let times = PhaseTimes {
+        setup: Duration::ZERO,
+        prove: start2.duration_since(start1),
+        verify: start3.duration_since(start2),
+    };
+    (times, proof)
+}
@@ -0,0 +1,33 @@
+//! Arbitrary bytes into `RangeProof::from_bytes`, and every proof that decodes into
+//! `verify_single` for a 32-bit range. Neither may panic; proofs with the wrong number
+//! of rounds or non-canonical scalars must come back as errors.
+#![no_main]
+
+use std::sync::OnceLock;
+
+use bulletproofs::{BulletproofGens, PedersenGens, RangeProof};
+use curve25519_dalek::ristretto::CompressedRistretto;
+use curve25519_dalek::scalar::Scalar;
+use labkit::context::ProofContext;
+use labkit::reference::{RANGE_BITS, RANGE_VALUE};
+use libfuzzer_sys::fuzz_target;
+
+/// Generators for one 32-bit proof and a commitment to `RANGE_VALUE`.
+fn statement() -> &'static (PedersenGens, BulletproofGens, CompressedRistretto) {
+    static STATEMENT: OnceLock<(PedersenGens, BulletproofGens, CompressedRistretto)> = OnceLock::new();
+    STATEMENT.get_or_init(|| {
+        let pc_gens = PedersenGens::default();
+        let commitment = pc_gens.commit(Scalar::from(RANGE_VALUE), Scalar::ONE).compress();
+        (pc_gens, BulletproofGens::new(RANGE_BITS, 1), commitment)
+    })
+}
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(proof) = RangeProof::from_bytes(data) else {
+        return;
+    };
+    let (pc_gens, bp_gens, commitment) = statement();
+    // The same domain as the rangeproof example, so its proofs get past the transcript
+    let context = ProofContext::new(b"range_proof", "");
+    let _ = proof.verify_single(bp_gens, pc_gens, &mut context.transcript(), commitment, RANGE_BITS);
+});
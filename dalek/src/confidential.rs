@@ -0,0 +1,315 @@
+//! Confidential transfers on Pedersen commitments.
+//!
+//! Every amount is committed as `C = v * B + r * B_blinding`. A transfer reveals only the input
+//! and output commitments and the public fee, together with
+//! - one aggregated range proof showing that every output lies in `[0, 2^64)`, and
+//! - a balance proof showing that the inputs equal the outputs plus the fee.
+//!
+//! Because the commitments are additively homomorphic,
+//! `sum(C_in) - sum(C_out) - fee * B` equals `(sum(r_in) - sum(r_out)) * B_blinding` exactly when the
+//! amounts balance. The balance proof is a Schnorr proof of knowledge of that
+//! discrete logarithm with respect to `B_blinding`. The input commitments are assumed to
+//! come from earlier transfers whose range proofs were already checked.
+//!
+//! Both proofs share one transcript, which starts from a `ProofContext`, so a transfer
+//! only verifies under the context (e.g. chain ID) it was created for.
+use bulletproofs::{BulletproofGens, PedersenGens, ProofError, RangeProof};
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::Identity;
+use merlin::Transcript;
+use rand::{CryptoRng, RngCore};
+
+use labkit::context::ProofContext;
+
+/// Bit size of every committed amount.
+pub const AMOUNT_BITS: usize = 64;
+
+/// The value and blinding factor behind a commitment, known only to the sender.
+#[derive(Clone, Copy, Debug)]
+pub struct Opening {
+    pub value: u64,
+    pub blinding: Scalar,
+}
+
+impl Opening {
+    /// An opening of `value` with a blinding factor drawn from `rng`.
+    pub fn random<R: RngCore + CryptoRng>(value: u64, rng: &mut R) -> Self {
+        Opening {
+            value,
+            blinding: Scalar::random(rng),
+        }
+    }
+}
+
+/// Schnorr proof of knowledge of `x` with `excess = x * B_blinding`.
+#[derive(Clone, Debug)]
+pub struct BalanceProof {
+    nonce_commitment: CompressedRistretto,
+    response: Scalar,
+}
+
+/// Everything the verifier sees of a transfer.
+#[derive(Clone, Debug)]
+pub struct Transfer {
+    pub inputs: Vec<CompressedRistretto>,
+    pub outputs: Vec<CompressedRistretto>,
+    pub fee: u64,
+    pub range_proof: RangeProof,
+    pub balance_proof: BalanceProof,
+}
+
+#[derive(Debug)]
+pub enum TransferError {
+    /// The inputs do not cover the outputs plus the fee.
+    Unbalanced,
+    /// A transfer needs at least one output.
+    NoOutputs,
+    RangeProof(ProofError),
+    InvalidCommitment,
+    BalanceProof,
+}
+
+impl From<ProofError> for TransferError {
+    fn from(e: ProofError) -> Self {
+        TransferError::RangeProof(e)
+    }
+}
+
+impl Transfer {
+    /// Size in bytes of the commitments, fee and both proofs.
+    pub fn serialized_size(&self) -> usize {
+        32 * (self.inputs.len() + self.outputs.len()) + 8 + self.range_proof.to_bytes().len() + 64
+    }
+}
+
+/// Number of range proof parties needed for `outputs` outputs. The aggregated range
+/// proof only takes a power of two, so the remaining parties are padded with the
+/// identity commitment `0 * B + 0 * B_blinding`.
+pub fn range_proof_parties(outputs: usize) -> usize {
+    outputs.next_power_of_two()
+}
+
+/// Starts the transcript shared by the range proof and the balance proof. It binds
+/// the context and the whole public statement, so neither proof can be reused in
+/// another transfer.
+fn transcript_for(
+    context: &ProofContext,
+    inputs: &[CompressedRistretto],
+    outputs: &[CompressedRistretto],
+    fee: u64,
+) -> Transcript {
+    let mut transcript = context.transcript();
+    transcript.append_message(b"dom-sep", b"confidential_transfer");
+    transcript.append_u64(b"inputs", inputs.len() as u64);
+    for c in inputs {
+        transcript.append_message(b"input", c.as_bytes());
+    }
+    transcript.append_u64(b"outputs", outputs.len() as u64);
+    for c in outputs {
+        transcript.append_message(b"output", c.as_bytes());
+    }
+    transcript.append_u64(b"fee", fee);
+    transcript
+}
+
+fn padded_outputs(outputs: &[CompressedRistretto]) -> Vec<CompressedRistretto> {
+    let mut padded = outputs.to_vec();
+    padded.resize(range_proof_parties(outputs.len()), RistrettoPoint::identity().compress());
+    padded
+}
+
+fn balance_challenge(transcript: &mut Transcript, nonce_commitment: &CompressedRistretto) -> Scalar {
+    transcript.append_message(b"balance_nonce", nonce_commitment.as_bytes());
+    let mut bytes = [0u8; 64];
+    transcript.challenge_bytes(b"balance_challenge", &mut bytes);
+    Scalar::from_bytes_mod_order_wide(&bytes)
+}
+
+/// `sum(inputs) - sum(outputs) - fee * B`, which is a multiple of `B_blinding` alone
+/// when the transfer balances.
+fn excess(
+    pc_gens: &PedersenGens,
+    inputs: &[CompressedRistretto],
+    outputs: &[CompressedRistretto],
+    fee: u64,
+) -> Result<RistrettoPoint, TransferError> {
+    let decompress = |c: &CompressedRistretto| c.decompress().ok_or(TransferError::InvalidCommitment);
+    let mut total = -(pc_gens.B * Scalar::from(fee));
+    for c in inputs {
+        total += decompress(c)?;
+    }
+    for c in outputs {
+        total -= decompress(c)?;
+    }
+    Ok(total)
+}
+
+/// Commits to `inputs` and `outputs` and proves that the outputs are in range and
+/// that the inputs pay for the outputs plus `fee`. `bp_gens` must have capacity for
+/// `AMOUNT_BITS` bits and `range_proof_parties(outputs.len())` parties. All of the
+/// prover's randomness comes from `rng`.
+pub fn prove_transfer<R: RngCore + CryptoRng>(
+    context: &ProofContext,
+    pc_gens: &PedersenGens,
+    bp_gens: &BulletproofGens,
+    inputs: &[Opening],
+    outputs: &[Opening],
+    fee: u64,
+    rng: &mut R,
+) -> Result<Transfer, TransferError> {
+    if outputs.is_empty() {
+        return Err(TransferError::NoOutputs);
+    }
+    let total_in: u128 = inputs.iter().map(|o| o.value as u128).sum();
+    let total_out: u128 = outputs.iter().map(|o| o.value as u128).sum::<u128>() + fee as u128;
+    if total_in != total_out {
+        return Err(TransferError::Unbalanced);
+    }
+
+    let commit = |o: &Opening| pc_gens.commit(Scalar::from(o.value), o.blinding).compress();
+    let input_commitments: Vec<_> = inputs.iter().map(commit).collect();
+    let output_commitments: Vec<_> = outputs.iter().map(commit).collect();
+    let mut transcript = transcript_for(context, &input_commitments, &output_commitments, fee);
+
+    // Padding parties commit to zero with a zero blinding, i.e. to the identity
+    let parties = range_proof_parties(outputs.len());
+    let mut values: Vec<u64> = outputs.iter().map(|o| o.value).collect();
+    let mut blindings: Vec<Scalar> = outputs.iter().map(|o| o.blinding).collect();
+    values.resize(parties, 0);
+    blindings.resize(parties, Scalar::ZERO);
+    let (range_proof, _) = RangeProof::prove_multiple_with_rng(
+        bp_gens,
+        pc_gens,
+        &mut transcript,
+        &values,
+        &blindings,
+        AMOUNT_BITS,
+        rng,
+    )?;
+
+    let excess_blinding: Scalar = inputs.iter().map(|o| o.blinding).sum::<Scalar>()
+        - outputs.iter().map(|o| o.blinding).sum::<Scalar>();
+    let nonce = Scalar::random(rng);
+    let nonce_commitment = (pc_gens.B_blinding * nonce).compress();
+    let challenge = balance_challenge(&mut transcript, &nonce_commitment);
+
+    Ok(Transfer {
+        inputs: input_commitments,
+        outputs: output_commitments,
+        fee,
+        range_proof,
+        balance_proof: BalanceProof {
+            nonce_commitment,
+            response: nonce + challenge * excess_blinding,
+        },
+    })
+}
+
+/// Checks a transfer using only its commitments, fee and proofs.
+pub fn verify_transfer(
+    context: &ProofContext,
+    pc_gens: &PedersenGens,
+    bp_gens: &BulletproofGens,
+    transfer: &Transfer,
+) -> Result<(), TransferError> {
+    if transfer.outputs.is_empty() {
+        return Err(TransferError::NoOutputs);
+    }
+    let mut transcript = transcript_for(context, &transfer.inputs, &transfer.outputs, transfer.fee);
+    transfer.range_proof.verify_multiple(
+        bp_gens,
+        pc_gens,
+        &mut transcript,
+        &padded_outputs(&transfer.outputs),
+        AMOUNT_BITS,
+    )?;
+
+    let excess = excess(pc_gens, &transfer.inputs, &transfer.outputs, transfer.fee)?;
+    let proof = &transfer.balance_proof;
+    let nonce_commitment = proof
+        .nonce_commitment
+        .decompress()
+        .ok_or(TransferError::InvalidCommitment)?;
+    let challenge = balance_challenge(&mut transcript, &proof.nonce_commitment);
+    if pc_gens.B_blinding * proof.response == nonce_commitment + excess * challenge {
+        Ok(())
+    } else {
+        Err(TransferError::BalanceProof)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::OsRng;
+
+    fn context() -> ProofContext {
+        ProofContext::new(b"test", "chain-1")
+    }
+
+    fn gens(outputs: usize) -> (PedersenGens, BulletproofGens) {
+        (PedersenGens::default(), BulletproofGens::new(AMOUNT_BITS, range_proof_parties(outputs)))
+    }
+
+    #[test]
+    fn test_transfer_verifies() {
+        let (pc_gens, bp_gens) = gens(3);
+        let inputs = [Opening::random(70, &mut OsRng), Opening::random(40, &mut OsRng)];
+        let outputs = [
+            Opening::random(50, &mut OsRng),
+            Opening::random(45, &mut OsRng),
+            Opening::random(10, &mut OsRng),
+        ];
+        let transfer = prove_transfer(&context(), &pc_gens, &bp_gens, &inputs, &outputs, 5, &mut OsRng).unwrap();
+        assert!(verify_transfer(&context(), &pc_gens, &bp_gens, &transfer).is_ok());
+    }
+
+    #[test]
+    fn test_unbalanced_transfer_is_refused() {
+        let (pc_gens, bp_gens) = gens(1);
+        let inputs = [Opening::random(100, &mut OsRng)];
+        let outputs = [Opening::random(99, &mut OsRng)];
+        assert!(matches!(
+            prove_transfer(&context(), &pc_gens, &bp_gens, &inputs, &outputs, 2, &mut OsRng),
+            Err(TransferError::Unbalanced)
+        ));
+    }
+
+    #[test]
+    fn test_tampered_transfer_fails() {
+        let (pc_gens, bp_gens) = gens(2);
+        let inputs = [Opening::random(100, &mut OsRng)];
+        let outputs = [Opening::random(60, &mut OsRng), Opening::random(39, &mut OsRng)];
+        let transfer = prove_transfer(&context(), &pc_gens, &bp_gens, &inputs, &outputs, 1, &mut OsRng).unwrap();
+
+        // Claiming a lower fee would mint one unit
+        let mut lower_fee = transfer.clone();
+        lower_fee.fee = 0;
+        assert!(verify_transfer(&context(), &pc_gens, &bp_gens, &lower_fee).is_err());
+
+        // Swapping in a commitment to a larger output breaks both proofs
+        let mut inflated = transfer.clone();
+        inflated.outputs[0] = pc_gens.commit(Scalar::from(61u64), outputs[0].blinding).compress();
+        assert!(verify_transfer(&context(), &pc_gens, &bp_gens, &inflated).is_err());
+
+        // A balance proof from another transfer does not carry over
+        let other = prove_transfer(&context(), &pc_gens, &bp_gens, &inputs, &outputs, 1, &mut OsRng).unwrap();
+        let mut mixed = transfer;
+        mixed.balance_proof = other.balance_proof;
+        assert!(matches!(
+            verify_transfer(&context(), &pc_gens, &bp_gens, &mixed),
+            Err(TransferError::BalanceProof)
+        ));
+    }
+
+    #[test]
+    fn test_transfer_from_another_chain_fails() {
+        let (pc_gens, bp_gens) = gens(1);
+        let inputs = [Opening::random(10, &mut OsRng)];
+        let outputs = [Opening::random(9, &mut OsRng)];
+        let transfer = prove_transfer(&context(), &pc_gens, &bp_gens, &inputs, &outputs, 1, &mut OsRng).unwrap();
+        let other_chain = ProofContext::new(b"test", "chain-2");
+        assert!(verify_transfer(&other_chain, &pc_gens, &bp_gens, &transfer).is_err());
+    }
+}
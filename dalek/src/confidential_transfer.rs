@@ -0,0 +1,77 @@
+extern crate bulletproofs;
+extern crate curve25519_dalek;
+
+use bulletproofs::{BulletproofGens, PedersenGens};
+use labkit::bench::{self, PhaseTimes};
+use dalek_lab::confidential::{self, Opening, AMOUNT_BITS};
+use labkit::context::ProofContext;
+use labkit::memory;
+use labkit::rng::ExampleRng;
+use std::time::Instant;
+
+// Counts the heap usage that `memory::measure` reports
+#[global_allocator]
+static ALLOCATOR: labkit::memory::PeakAllocator = labkit::memory::PeakAllocator;
+
+fn main() {
+    match bench::BenchConfig::from_args() {
+        Some(config) => {
+            bench::run("confidential_transfer", &config, prove_and_verify);
+        }
+        None => {
+            prove_and_verify();
+        }
+    }
+}
+
+/// Runs setup, proving and verification once and returns the time of each phase.
+fn prove_and_verify() -> PhaseTimes {
+    // OS randomness, or a reproducible stream with `--seed <n>`
+    let mut rng = ExampleRng::from_args();
+
+    // Two inputs pay for three outputs and a fee: 700 + 400 = 500 + 450 + 100 + 50
+    let inputs = [Opening::random(700, &mut rng), Opening::random(400, &mut rng)];
+    let outputs = [
+        Opening::random(500, &mut rng),
+        Opening::random(450, &mut rng),
+        Opening::random(100, &mut rng),
+    ];
+    let fee = 50;
+
+    let setup_time = Instant::now();
+    let ((pc_gens, bp_gens), _) = memory::measure("Setup", || {
+        (
+            PedersenGens::default(),
+            BulletproofGens::new(AMOUNT_BITS, confidential::range_proof_parties(outputs.len())),
+        )
+    });
+    let setup_duration = setup_time.elapsed();
+
+    let context = ProofContext::from_args(b"confidential_transfer");
+    println!("Creating transfer: {} inputs, {} outputs, fee {}", inputs.len(), outputs.len(), fee);
+    let proving_time = Instant::now();
+    let (transfer, _) = memory::measure("Prove", || {
+        confidential::prove_transfer(&context, &pc_gens, &bp_gens, &inputs, &outputs, fee, &mut rng)
+            .expect("transfer should balance")
+    });
+    let proving_duration = proving_time.elapsed();
+    println!("Proving time: {:?}", proving_duration);
+    println!("Range proof size: {} bytes", transfer.range_proof.to_bytes().len());
+    println!("Transfer size: {} bytes", transfer.serialized_size());
+
+    let verifying_time = Instant::now();
+    let (result, _) = memory::measure("Verify", || confidential::verify_transfer(&context, &pc_gens, &bp_gens, &transfer));
+    let verifying_duration = verifying_time.elapsed();
+    println!("Verification time: {:?}", verifying_duration);
+
+    match result {
+        Ok(_) => println!("Transfer verification successful!"),
+        Err(e) => println!("Transfer verification failed: {:?}", e),
+    }
+
+    PhaseTimes {
+        setup: setup_duration,
+        prove: proving_duration,
+        verify: verifying_duration,
+    }
+}
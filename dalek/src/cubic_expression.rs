@@ -0,0 +1,175 @@
+extern crate bulletproofs_r1cs;
+extern crate curve25519_dalek_ng;
+
+use bulletproofs_r1cs::r1cs::{ConstraintSystem, LinearCombination, Prover, R1CSProof, Verifier};
+use bulletproofs_r1cs::{BulletproofGens, PedersenGens};
+use curve25519_dalek_ng::ristretto::CompressedRistretto;
+use curve25519_dalek_ng::scalar::Scalar;
+use labkit::bench::{self, PhaseTimes};
+use labkit::context::ProofContext;
+use labkit::input::{self, CubicInput};
+use labkit::memory;
+use labkit::rng::{self, ExampleRng};
+use std::time::Instant;
+
+// Counts the heap usage that `memory::measure` reports
+#[global_allocator]
+static ALLOCATOR: labkit::memory::PeakAllocator = labkit::memory::PeakAllocator;
+
+/// Number of multipliers used by `cubic_gadget`.
+const MULTIPLIERS: usize = 2;
+
+/// Returns `x^3 + x + 5`, using two multipliers.
+fn cubic_gadget<CS: ConstraintSystem>(cs: &mut CS, x: LinearCombination) -> LinearCombination {
+    let (_, _, x_squared) = cs.multiply(x.clone(), x.clone());
+    let (_, _, x_cubed) = cs.multiply(x_squared.into(), x.clone());
+    x_cubed + x + Scalar::from(5u64)
+}
+
+/// Proves knowledge of the opening of `commitment` to an `x` with `x^3 + x + 5 = y`.
+fn prove(
+    context: &ProofContext,
+    pc_gens: &PedersenGens,
+    bp_gens: &BulletproofGens,
+    x: Scalar,
+    y: Scalar,
+    rng: &mut ExampleRng,
+) -> (R1CSProof, CompressedRistretto) {
+    let mut transcript = context.transcript();
+    let mut prover = Prover::new(pc_gens, &mut transcript);
+    let (commitment, x_var) = prover.commit(x, Scalar::random(rng));
+    let output = cubic_gadget(&mut prover, x_var.into());
+    prover.constrain(output - y);
+    let proof = prover.prove(bp_gens).expect("proof generation failed");
+    (proof, commitment)
+}
+
+fn verify(
+    context: &ProofContext,
+    pc_gens: &PedersenGens,
+    bp_gens: &BulletproofGens,
+    proof: &R1CSProof,
+    commitment: CompressedRistretto,
+    y: Scalar,
+) -> bool {
+    let mut transcript = context.transcript();
+    let mut verifier = Verifier::new(&mut transcript);
+    let x_var = verifier.commit(commitment);
+    let output = cubic_gadget(&mut verifier, x_var.into());
+    verifier.constrain(output - y);
+    verifier.verify(proof, pc_gens, bp_gens).is_ok()
+}
+
+fn main() {
+    rng::refuse_seed("the bulletproofs R1CS prover draws its blindings from thread_rng, so a seed could not fix its proof");
+    match bench::BenchConfig::from_args() {
+        Some(config) => {
+            bench::run("cubic_expression", &config, prove_and_verify);
+        }
+        None => {
+            prove_and_verify();
+        }
+    }
+}
+
+/// Runs setup, proving and verification once and returns the time of each phase.
+fn prove_and_verify() -> PhaseTimes {
+    let setup_time = Instant::now();
+    let ((pc_gens, bp_gens), _) = memory::measure("Setup", || {
+        (PedersenGens::default(), BulletproofGens::new(MULTIPLIERS.next_power_of_two(), 1))
+    });
+    let setup_duration = setup_time.elapsed();
+
+    // x = 3, y = 3^3 + 3 + 5 = 35, unless `--input <file>` gives another x in the schema of
+    // snarkjs/cubic_*/input.json
+    let x = Scalar::from(input::from_args::<CubicInput>().map_or(3, |input| input.x));
+    let y = x * x * x + x + Scalar::from(5u64);
+
+    let mut rng = ExampleRng::new(None);
+
+    let context = ProofContext::from_args(b"cubic_expression");
+    let proving_time = Instant::now();
+    let ((proof, commitment), _) = memory::measure("Prove", || prove(&context, &pc_gens, &bp_gens, x, y, &mut rng));
+    let proving_duration = proving_time.elapsed();
+    println!("Proving time: {:?}", proving_duration);
+    println!("Proof size: {} bytes", proof.serialized_size());
+
+    let verifying_time = Instant::now();
+    let (valid, _) = memory::measure("Verify", || verify(&context, &pc_gens, &bp_gens, &proof, commitment, y));
+    let verifying_duration = verifying_time.elapsed();
+    println!("Verification time: {:?}", verifying_duration);
+    assert!(valid, "proof should verify");
+    println!("Proof verification successful!");
+
+    PhaseTimes {
+        setup: setup_duration,
+        prove: proving_duration,
+        verify: verifying_duration,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use labkit::hiding::{self, Hiding};
+    use labkit::reference::{self, CUBIC_X};
+    use labkit::tamper;
+
+    #[test]
+    fn test_output_matches_reference() {
+        let context = ProofContext::new(b"cubic_expression", "");
+        let (pc_gens, bp_gens) = (PedersenGens::default(), BulletproofGens::new(MULTIPLIERS.next_power_of_two(), 1));
+        let y = reference::cubic(CUBIC_X);
+        let (x, y) = (Scalar::from(CUBIC_X), Scalar::from(y));
+        let (proof, commitment) = prove(&context, &pc_gens, &bp_gens, x, y, &mut ExampleRng::new(None));
+        assert!(verify(&context, &pc_gens, &bp_gens, &proof, commitment, y));
+    }
+
+    #[test]
+    fn test_wrong_outputs_are_rejected() {
+        let context = ProofContext::new(b"cubic_expression", "");
+        let (pc_gens, bp_gens) = (PedersenGens::default(), BulletproofGens::new(MULTIPLIERS.next_power_of_two(), 1));
+        let x = Scalar::from(CUBIC_X);
+        let mut rng = ExampleRng::new(None);
+        let y = Scalar::from(reference::cubic(CUBIC_X));
+        let (proof, commitment) = prove(&context, &pc_gens, &bp_gens, x, y, &mut rng);
+        for y in reference::wrong_cubic_outputs() {
+            let y = Scalar::from(y);
+            // Verifying an honest proof against a wrong output
+            assert!(!verify(&context, &pc_gens, &bp_gens, &proof, commitment, y));
+            // Proving a wrong output: the prover does not check the witness, the verifier must
+            let (proof, commitment) = prove(&context, &pc_gens, &bp_gens, x, y, &mut rng);
+            assert!(!verify(&context, &pc_gens, &bp_gens, &proof, commitment, y));
+        }
+    }
+
+    #[test]
+    fn test_tampered_proofs_are_rejected() {
+        let context = ProofContext::new(b"cubic_expression", "");
+        let (pc_gens, bp_gens) = (PedersenGens::default(), BulletproofGens::new(MULTIPLIERS.next_power_of_two(), 1));
+        let mut rng = ExampleRng::new(None);
+        let mut prove_bytes = |x: u64| {
+            let (proof, commitment) =
+                prove(&context, &pc_gens, &bp_gens, Scalar::from(x), Scalar::from(reference::cubic(x)), &mut rng);
+            (proof.to_bytes(), commitment)
+        };
+        let (proof, commitment) = prove_bytes(CUBIC_X);
+        let (other, _) = prove_bytes(CUBIC_X + 1);
+
+        let y = Scalar::from(reference::cubic(CUBIC_X));
+        tamper::assert_rejects_tampering(&proof, &other, |bytes| {
+            R1CSProof::from_bytes(bytes).is_ok_and(|proof| verify(&context, &pc_gens, &bp_gens, &proof, commitment, y))
+        });
+    }
+
+    #[test]
+    fn test_proofs_are_randomized() {
+        let context = ProofContext::new(b"cubic_expression", "");
+        let (pc_gens, bp_gens) = (PedersenGens::default(), BulletproofGens::new(MULTIPLIERS.next_power_of_two(), 1));
+        let (x, y) = (Scalar::from(CUBIC_X), Scalar::from(reference::cubic(CUBIC_X)));
+        let prove_bytes = |rng: &mut ExampleRng| prove(&context, &pc_gens, &bp_gens, x, y, rng).0.to_bytes();
+        assert_eq!(hiding::check(|| prove_bytes(&mut ExampleRng::new(None))), Hiding::Randomized);
+        // The R1CS prover mixes `thread_rng` into its blindings, so even a seeded RNG gives new proofs
+        assert_eq!(hiding::check(|| prove_bytes(&mut ExampleRng::new(Some(1)))), Hiding::Randomized);
+    }
+}
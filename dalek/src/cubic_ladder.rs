@@ -0,0 +1,136 @@
+extern crate bulletproofs_r1cs;
+extern crate curve25519_dalek_ng;
+
+use bulletproofs_r1cs::r1cs::{ConstraintSystem, LinearCombination, Prover, R1CSProof, Verifier};
+use bulletproofs_r1cs::{BulletproofGens, PedersenGens};
+use curve25519_dalek_ng::ristretto::CompressedRistretto;
+use curve25519_dalek_ng::scalar::Scalar;
+use labkit::bench::{PhaseTimes, SizedRun};
+use labkit::context::ProofContext;
+use labkit::rng::{self, ExampleRng};
+use labkit::ladder;
+use labkit::memory;
+use std::time::Instant;
+
+// Counts the heap usage that `memory::measure` reports
+#[global_allocator]
+static ALLOCATOR: labkit::memory::PeakAllocator = labkit::memory::PeakAllocator;
+
+/// Chains `x_{i+1} = x_i^3 + x_i + 5` for `steps` steps starting from `x0` and
+/// returns `x_steps`. Every step uses two multipliers.
+fn ladder_gadget<CS: ConstraintSystem>(cs: &mut CS, x0: LinearCombination, steps: usize) -> LinearCombination {
+    (0..steps).fold(x0, |x, _| {
+        let (_, _, x_squared) = cs.multiply(x.clone(), x.clone());
+        let (_, _, x_cubed) = cs.multiply(x_squared.into(), x.clone());
+        x_cubed + x + Scalar::from(ladder::STEP_CONSTANT)
+    })
+}
+
+/// Computes `x_steps` natively.
+fn ladder_output(x0: Scalar, steps: usize) -> Scalar {
+    (0..steps).fold(x0, |x, _| x * x * x + x + Scalar::from(ladder::STEP_CONSTANT))
+}
+
+/// Proves knowledge of the opening of `commitment` to an `x0` with `ladder(x0) = y`.
+fn prove(
+    context: &ProofContext,
+    pc_gens: &PedersenGens,
+    bp_gens: &BulletproofGens,
+    x0: Scalar,
+    y: Scalar,
+    steps: usize,
+    rng: &mut ExampleRng,
+) -> (R1CSProof, CompressedRistretto) {
+    let mut transcript = context.transcript();
+    let mut prover = Prover::new(pc_gens, &mut transcript);
+    let (commitment, x0_var) = prover.commit(x0, Scalar::random(rng));
+    let output = ladder_gadget(&mut prover, x0_var.into(), steps);
+    prover.constrain(output - y);
+    let proof = prover.prove(bp_gens).expect("proof generation failed");
+    (proof, commitment)
+}
+
+fn verify(
+    context: &ProofContext,
+    pc_gens: &PedersenGens,
+    bp_gens: &BulletproofGens,
+    proof: &R1CSProof,
+    commitment: CompressedRistretto,
+    y: Scalar,
+    steps: usize,
+) -> bool {
+    let mut transcript = context.transcript();
+    let mut verifier = Verifier::new(&mut transcript);
+    let x0_var = verifier.commit(commitment);
+    let output = ladder_gadget(&mut verifier, x0_var.into(), steps);
+    verifier.constrain(output - y);
+    verifier.verify(proof, pc_gens, bp_gens).is_ok()
+}
+
+fn main() {
+    rng::refuse_seed("the bulletproofs R1CS prover draws its blindings from thread_rng, so a seed could not fix its proof");
+    let log_sizes = ladder::log_sizes_from_args();
+    ladder::sweep("cubic_ladder", "multipliers", &log_sizes, prove_and_verify);
+}
+
+/// Runs setup, proving and verification for a ladder of `steps` steps.
+fn prove_and_verify(steps: usize) -> SizedRun {
+    let multipliers = steps * ladder::CONSTRAINTS_PER_STEP;
+
+    let setup_time = Instant::now();
+    let ((pc_gens, bp_gens), _) = memory::measure("Setup", || {
+        (PedersenGens::default(), BulletproofGens::new(multipliers.next_power_of_two(), 1))
+    });
+    let setup_duration = setup_time.elapsed();
+
+    let mut rng = ExampleRng::new(None);
+    let x0 = Scalar::random(&mut rng);
+    let y = ladder_output(x0, steps);
+
+    let context = ProofContext::from_args(b"cubic_ladder");
+    let proving_time = Instant::now();
+    let ((proof, commitment), prove_memory) =
+        memory::measure("Prove", || prove(&context, &pc_gens, &bp_gens, x0, y, steps, &mut rng));
+    let proving_duration = proving_time.elapsed();
+    println!("Proof size: {} bytes", proof.serialized_size());
+
+    let verifying_time = Instant::now();
+    let (valid, _) = memory::measure("Verify", || verify(&context, &pc_gens, &bp_gens, &proof, commitment, y, steps));
+    let verifying_duration = verifying_time.elapsed();
+    assert!(valid, "proof should verify");
+    println!("Proof verification successful!");
+
+    SizedRun {
+        size: multipliers,
+        times: PhaseTimes {
+            setup: setup_duration,
+            prove: proving_duration,
+            verify: verifying_duration,
+        },
+        proof_size: proof.serialized_size(),
+        prove_memory,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ladder_proves_its_output() {
+        let steps = ladder::steps(4);
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(steps * ladder::CONSTRAINTS_PER_STEP, 1);
+        let context = ProofContext::new(b"cubic_ladder", "");
+        let mut rng = ExampleRng::new(Some(0));
+
+        let x0 = Scalar::from(3u64);
+        let y = ladder_output(x0, steps);
+        let (proof, commitment) = prove(&context, &pc_gens, &bp_gens, x0, y, steps, &mut rng);
+        assert!(verify(&context, &pc_gens, &bp_gens, &proof, commitment, y, steps));
+        assert!(
+            !verify(&context, &pc_gens, &bp_gens, &proof, commitment, y + Scalar::one(), steps),
+            "a wrong x_N should be rejected"
+        );
+    }
+}
@@ -0,0 +1,2 @@
+pub mod confidential;
+pub mod sha256_gadget;
@@ -1,44 +1,79 @@
 extern crate bulletproofs;
 extern crate curve25519_dalek;
-extern crate rand;
 
 use bulletproofs::{
     BulletproofGens, RangeProof, PedersenGens,
 };
 use curve25519_dalek::scalar::Scalar;
-use rand::rngs::OsRng;
-use merlin::Transcript;
+use labkit::bench::{self, PhaseTimes};
+use labkit::context::ProofContext;
+use labkit::input::{self, RangeInput};
+use labkit::rng::ExampleRng;
+use labkit::corpus;
+use labkit::memory;
 use std::time::Instant;
 
+// Counts the heap usage that `memory::measure` reports
+#[global_allocator]
+static ALLOCATOR: labkit::memory::PeakAllocator = labkit::memory::PeakAllocator;
+
 fn main() {
-    // 1. Initialize random number generator
-    let mut rng = OsRng;
+    match bench::BenchConfig::from_args() {
+        Some(config) => {
+            bench::run("rangeproof", &config, prove_and_verify);
+        }
+        None => {
+            prove_and_verify();
+        }
+    }
+}
 
-    // 2. Generate base generators for Pedersen commitment
-    let pedersen_gens = PedersenGens::default();
+/// Runs setup, proving and verification once and returns the time of each phase.
+fn prove_and_verify() -> PhaseTimes {
+    // 1. Initialize random number generator: OS randomness, or a reproducible stream
+    //    with `--seed <n>`
+    let mut rng = ExampleRng::from_args();
 
+    // 2. Generate base generators for Pedersen commitment
     // 3. Create BulletproofGens, specify maximum number of proofs
-    let bulletproof_gens = BulletproofGens::new(32, 1);  // 2^32 range
+    let setup_time = Instant::now();
+    let ((pedersen_gens, bulletproof_gens), _) = memory::measure("Setup", || {
+        (PedersenGens::default(), BulletproofGens::new(32, 1))  // 2^32 range
+    });
+    let setup_duration = setup_time.elapsed();
 
-    // 4. Choose a value for range proof (e.g. a value between 0 and 2^32-1)
-    let value: u32 = 1234567890;  // Make sure this value is between 0 and 2^32-1
+    // 4. Choose a value for range proof (e.g. a value between 0 and 2^32-1), or read it
+    //    with `--input <file>` in the schema of snarkjs/rangeproof_*/input.json
+    let value: u64 = match input::from_args::<RangeInput>() {
+        Some(input) => {
+            assert_eq!(input.bits, 32, "the example proves 32-bit ranges");
+            input.value
+        }
+        None => 1234567890,  // Make sure this value is between 0 and 2^32-1
+    };
     
     // 5. Create random number for blinding factor
     let blinding = Scalar::random(&mut rng);
 
     println!("Creating proof for value: {} (range: 0 to {})", value, u32::MAX);
 
+    // Bind both transcripts to the application context (`--context`)
+    let context = ProofContext::from_args(b"range_proof");
+
     // Generate proof
     let proving_time = Instant::now();
-    let mut prover_transcript = Transcript::new(b"range_proof");
-    let (proof, committed_value) = RangeProof::prove_single(
-        &bulletproof_gens,
-        &pedersen_gens,
-        &mut prover_transcript,
-        value as u64,  // Convert to u64
-        &blinding,     // Blinding factor
-        32,  // Change bit size to 32
-    ).expect("Proof generation failed");
+    let mut prover_transcript = context.transcript();
+    let ((proof, committed_value), _) = memory::measure("Prove", || {
+        RangeProof::prove_single_with_rng(
+            &bulletproof_gens,
+            &pedersen_gens,
+            &mut prover_transcript,
+            value,
+            &blinding,     // Blinding factor
+            32,  // Change bit size to 32
+            &mut rng,
+        ).expect("Proof generation failed")
+    });
     let proving_duration = proving_time.elapsed();
 
     // Calculate proof size
@@ -48,14 +83,16 @@ fn main() {
 
     // Verify proof
     let verifying_time = Instant::now();
-    let mut verifier_transcript = Transcript::new(b"range_proof");
-    let result = proof.verify_single(
-        &bulletproof_gens,
-        &pedersen_gens,
-        &mut verifier_transcript,
-        &committed_value,
-        32,  // Change bit size to 32
-    );
+    let mut verifier_transcript = context.transcript();
+    let (result, _) = memory::measure("Verify", || {
+        proof.verify_single(
+            &bulletproof_gens,
+            &pedersen_gens,
+            &mut verifier_transcript,
+            &committed_value,
+            32,  // Change bit size to 32
+        )
+    });
     let verifying_duration = verifying_time.elapsed();
     println!("Verification time: {:?}", verifying_duration);
 
@@ -63,4 +100,110 @@ fn main() {
         Ok(_) => println!("Proof verification successful!"),
         Err(e) => println!("Proof verification failed: {:?}", e),
     }
+
+    // `--save-corpus <dir>`: seed the fuzz target with this proof
+    corpus::save_from_args("range_proof", "rangeproof", &proof.to_bytes());
+
+    PhaseTimes {
+        setup: setup_duration,
+        prove: proving_duration,
+        verify: verifying_duration,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use labkit::hiding::{self, Hiding};
+    use labkit::reference::{self, RANGE_BITS, RANGE_VALUE};
+    use labkit::tamper;
+
+    /// Proves `value` in `[0, 2^RANGE_BITS)` and returns whether the proof verifies.
+    fn prove_and_check(value: u64) -> bool {
+        let context = ProofContext::new(b"range_proof", "");
+        let (pc_gens, bp_gens) = (PedersenGens::default(), BulletproofGens::new(RANGE_BITS, 1));
+        let blinding = Scalar::random(&mut ExampleRng::new(None));
+        let Ok((proof, commitment)) =
+            RangeProof::prove_single(&bp_gens, &pc_gens, &mut context.transcript(), value, &blinding, RANGE_BITS)
+        else {
+            return false;
+        };
+        proof
+            .verify_single(&bp_gens, &pc_gens, &mut context.transcript(), &commitment, RANGE_BITS)
+            .is_ok()
+    }
+
+    #[test]
+    fn test_value_in_range_is_accepted() {
+        assert!(prove_and_check(RANGE_VALUE));
+    }
+
+    #[test]
+    fn test_out_of_range_values_are_rejected() {
+        for value in reference::OUT_OF_RANGE_VALUES {
+            assert!(!prove_and_check(value), "{} should be rejected", value);
+        }
+    }
+
+    #[test]
+    fn test_tampered_proofs_are_rejected() {
+        let context = ProofContext::new(b"range_proof", "");
+        let (pc_gens, bp_gens) = (PedersenGens::default(), BulletproofGens::new(RANGE_BITS, 1));
+        let prove_bytes = |value: u64| {
+            let blinding = Scalar::random(&mut ExampleRng::new(None));
+            let (proof, commitment) =
+                RangeProof::prove_single(&bp_gens, &pc_gens, &mut context.transcript(), value, &blinding, RANGE_BITS)
+                    .unwrap();
+            (proof.to_bytes(), commitment)
+        };
+        let (proof, commitment) = prove_bytes(RANGE_VALUE);
+        let (other, _) = prove_bytes(RANGE_VALUE + 1);
+
+        tamper::assert_rejects_tampering(&proof, &other, |bytes| {
+            RangeProof::from_bytes(bytes).is_ok_and(|proof| {
+                proof
+                    .verify_single(&bp_gens, &pc_gens, &mut context.transcript(), &commitment, RANGE_BITS)
+                    .is_ok()
+            })
+        });
+    }
+
+    #[test]
+    fn test_proofs_are_randomized() {
+        let context = ProofContext::new(b"range_proof", "");
+        let (pc_gens, bp_gens) = (PedersenGens::default(), BulletproofGens::new(RANGE_BITS, 1));
+        // The same commitment both times, so only the prover's own randomness can differ
+        let blinding = Scalar::random(&mut ExampleRng::new(None));
+        let prove_bytes = |rng: &mut ExampleRng| {
+            let mut transcript = context.transcript();
+            let n = RANGE_BITS;
+            RangeProof::prove_single_with_rng(&bp_gens, &pc_gens, &mut transcript, RANGE_VALUE, &blinding, n, rng)
+                .unwrap()
+                .0
+                .to_bytes()
+        };
+        assert_eq!(hiding::check(|| prove_bytes(&mut ExampleRng::new(None))), Hiding::Randomized);
+        // The range prover draws only from the RNG it is given, so `--seed` repeats the proof
+        assert_eq!(hiding::check(|| prove_bytes(&mut ExampleRng::new(Some(1)))), Hiding::Deterministic);
+    }
+
+    #[test]
+    fn test_mismatched_context_fails() {
+        let (pc_gens, bp_gens) = (PedersenGens::default(), BulletproofGens::new(RANGE_BITS, 1));
+        let blinding = Scalar::random(&mut ExampleRng::new(None));
+        let context = ProofContext::new(b"range_proof", "chain-1");
+        let (proof, commitment) =
+            RangeProof::prove_single(&bp_gens, &pc_gens, &mut context.transcript(), RANGE_VALUE, &blinding, RANGE_BITS)
+                .unwrap();
+
+        let verify = |context: &ProofContext| {
+            proof
+                .verify_single(&bp_gens, &pc_gens, &mut context.transcript(), &commitment, RANGE_BITS)
+                .is_ok()
+        };
+        assert!(verify(&context));
+        assert!(!verify(&ProofContext::new(b"range_proof", "chain-2")));
+        assert!(!verify(&ProofContext::new(b"range_proof", "")));
+        assert!(!verify(&ProofContext::new(b"other_app", "chain-1")));
+    }
 }
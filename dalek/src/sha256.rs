@@ -0,0 +1,171 @@
+extern crate bulletproofs_r1cs;
+extern crate curve25519_dalek_ng;
+
+use bulletproofs_r1cs::r1cs::{ConstraintSystem, Prover, R1CSProof, Verifier};
+use bulletproofs_r1cs::{BulletproofGens, PedersenGens};
+use labkit::bench::{self, PhaseTimes};
+use labkit::context::ProofContext;
+use labkit::input::{self, Sha256Input};
+use dalek_lab::sha256_gadget;
+use labkit::memory;
+use labkit::rng;
+use merlin::Transcript;
+use sha2::{Digest, Sha256};
+use std::time::Instant;
+
+// Counts the heap usage that `memory::measure` reports
+#[global_allocator]
+static ALLOCATOR: labkit::memory::PeakAllocator = labkit::memory::PeakAllocator;
+
+/// Proves knowledge of a `message` whose SHA-256 digest is the public `hash`.
+fn prove(
+    context: &ProofContext,
+    pc_gens: &PedersenGens,
+    bp_gens: &BulletproofGens,
+    message: &[u8],
+    hash: &[u8; 32],
+) -> R1CSProof {
+    let mut transcript = context.transcript();
+    let mut prover = Prover::new(pc_gens, &mut transcript);
+    let digest = sha256_gadget::sha256(&mut prover, Some(message), message.len()).expect("synthesis failed");
+    sha256_gadget::enforce_digest(&mut prover, &digest, hash);
+    prover.prove(bp_gens).expect("proof generation failed")
+}
+
+fn verify(
+    context: &ProofContext,
+    pc_gens: &PedersenGens,
+    bp_gens: &BulletproofGens,
+    proof: &R1CSProof,
+    len: usize,
+    hash: &[u8; 32],
+) -> bool {
+    let mut transcript = context.transcript();
+    let mut verifier = Verifier::new(&mut transcript);
+    let digest = match sha256_gadget::sha256(&mut verifier, None, len) {
+        Ok(digest) => digest,
+        Err(_) => return false,
+    };
+    sha256_gadget::enforce_digest(&mut verifier, &digest, hash);
+    verifier.verify(proof, pc_gens, bp_gens).is_ok()
+}
+
+/// Number of multipliers needed to hash a `len`-byte message.
+fn count_multipliers(len: usize) -> usize {
+    let mut transcript = Transcript::new(b"sha256");
+    let mut verifier = Verifier::new(&mut transcript);
+    sha256_gadget::sha256(&mut verifier, None, len).expect("synthesis failed");
+    verifier.metrics().multipliers
+}
+
+fn main() {
+    rng::refuse_seed("the bulletproofs R1CS prover draws its blindings from thread_rng, so a seed could not fix its proof");
+    // `--input <file>` replaces the message, in the schema of snarkjs/sha256_*/input.json
+    let message = input::from_args::<Sha256Input>().map_or(b"Hello, World!".to_vec(), |input| input.message);
+    let message = message.as_slice();
+    match bench::BenchConfig::from_args() {
+        Some(config) => {
+            bench::run("sha256", &config, || prove_and_verify(message));
+        }
+        None => {
+            prove_and_verify(message);
+        }
+    }
+}
+
+/// Runs setup, proving and verification once and returns the time of each phase.
+fn prove_and_verify(message: &[u8]) -> PhaseTimes {
+    let hash: [u8; 32] = Sha256::digest(message).into();
+
+    // The generators must cover every multiplier, so the circuit is synthesized once
+    // without a witness to count them
+    let setup_time = Instant::now();
+    let ((pc_gens, bp_gens, multipliers), _) = memory::measure("Setup", || {
+        let multipliers = count_multipliers(message.len());
+        (PedersenGens::default(), BulletproofGens::new(multipliers.next_power_of_two(), 1), multipliers)
+    });
+    let setup_duration = setup_time.elapsed();
+    println!("Number of multipliers: {}", multipliers);
+
+    let context = ProofContext::from_args(b"sha256");
+    let proving_time = Instant::now();
+    let (proof, _) = memory::measure("Prove", || prove(&context, &pc_gens, &bp_gens, message, &hash));
+    let proving_duration = proving_time.elapsed();
+    println!("Proving time: {:?}", proving_duration);
+    println!("Proof size: {} bytes", proof.serialized_size());
+
+    let verifying_time = Instant::now();
+    let (valid, _) = memory::measure("Verify", || verify(&context, &pc_gens, &bp_gens, &proof, message.len(), &hash));
+    let verifying_duration = verifying_time.elapsed();
+    println!("Verification time: {:?}", verifying_duration);
+    assert!(valid, "proof should verify");
+    println!("Proof verification successful!");
+
+    PhaseTimes {
+        setup: setup_duration,
+        prove: proving_duration,
+        verify: verifying_duration,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use labkit::reference::{self, SHA256_MESSAGE};
+    use labkit::tamper;
+    use std::sync::OnceLock;
+
+    fn context() -> ProofContext {
+        ProofContext::new(b"sha256", "")
+    }
+
+    fn gens() -> (PedersenGens, BulletproofGens) {
+        let multipliers = count_multipliers(SHA256_MESSAGE.len());
+        (PedersenGens::default(), BulletproofGens::new(multipliers.next_power_of_two(), 1))
+    }
+
+    fn prove_bytes(message: &[u8]) -> Vec<u8> {
+        let (pc_gens, bp_gens) = gens();
+        prove(&context(), &pc_gens, &bp_gens, message, &reference::sha256(message)).to_bytes()
+    }
+
+    /// Serialized proof for `SHA256_MESSAGE`, shared by the tests as proving takes
+    /// minutes in debug builds.
+    fn reference_proof() -> &'static [u8] {
+        static PROOF: OnceLock<Vec<u8>> = OnceLock::new();
+        PROOF.get_or_init(|| prove_bytes(SHA256_MESSAGE))
+    }
+
+    /// Whether `proof` deserializes and verifies against `hash`.
+    fn verify_bytes(proof: &[u8], hash: &[u8; 32]) -> bool {
+        let (pc_gens, bp_gens) = gens();
+        R1CSProof::from_bytes(proof)
+            .is_ok_and(|proof| verify(&context(), &pc_gens, &bp_gens, &proof, SHA256_MESSAGE.len(), hash))
+    }
+
+    #[test]
+    fn test_digest_matches_reference() {
+        let pc_gens = PedersenGens::default();
+        let mut transcript = Transcript::new(b"sha256");
+        let mut prover = Prover::new(&pc_gens, &mut transcript);
+        let digest = sha256_gadget::sha256(&mut prover, Some(SHA256_MESSAGE), SHA256_MESSAGE.len()).unwrap();
+        let bytes: Vec<u8> = digest.iter().flat_map(|w| w.value().unwrap().to_be_bytes()).collect();
+        assert_eq!(bytes, reference::sha256(SHA256_MESSAGE));
+    }
+
+    #[test]
+    fn test_wrong_digests_are_rejected() {
+        assert!(verify_bytes(reference_proof(), &reference::sha256(SHA256_MESSAGE)));
+        for digest in reference::wrong_digests() {
+            assert!(!verify_bytes(reference_proof(), &digest));
+        }
+    }
+
+    #[test]
+    fn test_tampered_proofs_are_rejected() {
+        // Another message of the same length, so only the digest differs
+        let other = prove_bytes(b"Hello, World?");
+        let hash = reference::sha256(SHA256_MESSAGE);
+        tamper::assert_rejects_tampering(reference_proof(), &other, |bytes| verify_bytes(bytes, &hash));
+    }
+}
@@ -0,0 +1,245 @@
+//! Bit-decomposed SHA-256 for the Bulletproofs R1CS API.
+//!
+//! A 32-bit word is a vector of 32 bit-valued linear combinations (least significant
+//! bit first) together with the value it takes in the prover's assignment (`None` on
+//! the verifier side). Rotations and shifts only reorder bits. XOR, AND, Ch and Maj cost
+//! one or two multipliers per bit. Addition modulo 2^32 allocates the 32 result bits
+//! plus a few carry bits and checks them with a single linear constraint. One
+//! compression takes about 27k multipliers.
+use bulletproofs_r1cs::r1cs::{ConstraintSystem, LinearCombination, R1CSError, Variable};
+use curve25519_dalek_ng::scalar::Scalar;
+
+const IV: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+const K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// A 32-bit word in the constraint system.
+#[derive(Clone)]
+pub struct Word {
+    bits: Vec<LinearCombination>,
+    value: Option<u32>,
+}
+
+/// Allocates a variable constrained to 0 or 1, using one multiplier: `b * (1 - b) = 0`.
+pub fn alloc_bit<CS: ConstraintSystem>(cs: &mut CS, value: Option<bool>) -> Result<Variable, R1CSError> {
+    let (b, not_b, product) = cs.allocate_multiplier(value.map(|v| {
+        let b = Scalar::from(v as u64);
+        (b, Scalar::one() - b)
+    }))?;
+    cs.constrain(product.into());
+    cs.constrain(b + not_b - Scalar::one());
+    Ok(b)
+}
+
+fn xor_bit<CS: ConstraintSystem>(cs: &mut CS, a: &LinearCombination, b: &LinearCombination) -> LinearCombination {
+    let (_, _, ab) = cs.multiply(a.clone(), b.clone());
+    a.clone() + b.clone() - ab * Scalar::from(2u64)
+}
+
+impl Word {
+    pub fn constant(value: u32) -> Self {
+        Word {
+            bits: (0..32).map(|i| Scalar::from((value >> i) as u64 & 1).into()).collect(),
+            value: Some(value),
+        }
+    }
+
+    /// Allocates 32 boolean-constrained bits holding `value`.
+    pub fn alloc<CS: ConstraintSystem>(cs: &mut CS, value: Option<u32>) -> Result<Self, R1CSError> {
+        let bits = (0..32)
+            .map(|i| alloc_bit(cs, value.map(|v| (v >> i) & 1 == 1)).map(LinearCombination::from))
+            .collect::<Result<_, _>>()?;
+        Ok(Word { bits, value })
+    }
+
+    /// Builds a word from 32 bits given most significant bit first.
+    fn from_be_bits(bits: &[(LinearCombination, Option<bool>)]) -> Self {
+        let value = bits
+            .iter()
+            .try_fold(0u32, |acc, (_, bit)| bit.map(|b| (acc << 1) | b as u32));
+        Word {
+            bits: bits.iter().rev().map(|(lc, _)| lc.clone()).collect(),
+            value,
+        }
+    }
+
+    pub fn value(&self) -> Option<u32> {
+        self.value
+    }
+
+    pub fn bits(&self) -> &[LinearCombination] {
+        &self.bits
+    }
+
+    fn rotr(&self, n: usize) -> Self {
+        Word {
+            bits: (0..32).map(|i| self.bits[(i + n) % 32].clone()).collect(),
+            value: self.value.map(|v| v.rotate_right(n as u32)),
+        }
+    }
+
+    fn shr(&self, n: usize) -> Self {
+        Word {
+            bits: (0..32)
+                .map(|i| self.bits.get(i + n).cloned().unwrap_or_default())
+                .collect(),
+            value: self.value.map(|v| v >> n),
+        }
+    }
+
+    fn xor<CS: ConstraintSystem>(&self, cs: &mut CS, other: &Word) -> Self {
+        Word {
+            bits: self.bits.iter().zip(&other.bits).map(|(a, b)| xor_bit(cs, a, b)).collect(),
+            value: self.value.zip(other.value).map(|(a, b)| a ^ b),
+        }
+    }
+
+    fn xor3<CS: ConstraintSystem>(cs: &mut CS, a: &Word, b: &Word, c: &Word) -> Self {
+        a.xor(cs, b).xor(cs, c)
+    }
+
+    /// `Ch(e, f, g) = (e & f) ^ (!e & g)`, computed per bit as `e * (f - g) + g`.
+    fn ch<CS: ConstraintSystem>(cs: &mut CS, e: &Word, f: &Word, g: &Word) -> Self {
+        let bits = (0..32)
+            .map(|i| {
+                let (_, _, t) = cs.multiply(e.bits[i].clone(), f.bits[i].clone() - g.bits[i].clone());
+                t + g.bits[i].clone()
+            })
+            .collect();
+        let value = match (e.value, f.value, g.value) {
+            (Some(e), Some(f), Some(g)) => Some((e & f) ^ (!e & g)),
+            _ => None,
+        };
+        Word { bits, value }
+    }
+
+    /// `Maj(a, b, c)`, computed per bit as `a * (b + c - 2bc) + bc`.
+    fn maj<CS: ConstraintSystem>(cs: &mut CS, a: &Word, b: &Word, c: &Word) -> Self {
+        let bits = (0..32)
+            .map(|i| {
+                let (_, _, bc) = cs.multiply(b.bits[i].clone(), c.bits[i].clone());
+                let b_xor_c = b.bits[i].clone() + c.bits[i].clone() - bc * Scalar::from(2u64);
+                let (_, _, t) = cs.multiply(a.bits[i].clone(), b_xor_c);
+                t + bc
+            })
+            .collect();
+        let value = match (a.value, b.value, c.value) {
+            (Some(a), Some(b), Some(c)) => Some((a & b) ^ (a & c) ^ (b & c)),
+            _ => None,
+        };
+        Word { bits, value }
+    }
+
+    /// Adds `words` and `constant` modulo 2^32.
+    fn add<CS: ConstraintSystem>(cs: &mut CS, words: &[&Word], constant: u32) -> Result<Self, R1CSError> {
+        let total: Option<u64> = words
+            .iter()
+            .try_fold(constant as u64, |acc, w| w.value.map(|v| acc + v as u64));
+        let max_carry = (words.len() as u64 * u32::MAX as u64 + constant as u64) >> 32;
+        let carry_bits = 64 - max_carry.leading_zeros() as usize;
+
+        let result = Word::alloc(cs, total.map(|t| t as u32))?;
+        let mut lc: LinearCombination = Scalar::from(constant as u64).into();
+        for word in words {
+            for (i, bit) in word.bits.iter().enumerate() {
+                lc = lc + bit.clone() * Scalar::from(1u64 << i);
+            }
+        }
+        for (i, bit) in result.bits.iter().enumerate() {
+            lc = lc - bit.clone() * Scalar::from(1u64 << i);
+        }
+        for j in 0..carry_bits {
+            let carry = alloc_bit(cs, total.map(|t| (t >> (32 + j)) & 1 == 1))?;
+            lc = lc - carry * Scalar::from(1u64 << (32 + j));
+        }
+        cs.constrain(lc);
+        Ok(result)
+    }
+}
+
+/// The SHA-256 compression function applied to `state` and a 16-word `block`.
+pub fn compress<CS: ConstraintSystem>(cs: &mut CS, state: &[Word], block: &[Word]) -> Result<Vec<Word>, R1CSError> {
+    let mut w = block.to_vec();
+    for t in 16..64 {
+        let s0 = Word::xor3(cs, &w[t - 15].rotr(7), &w[t - 15].rotr(18), &w[t - 15].shr(3));
+        let s1 = Word::xor3(cs, &w[t - 2].rotr(17), &w[t - 2].rotr(19), &w[t - 2].shr(10));
+        let next = Word::add(cs, &[&w[t - 16], &s0, &w[t - 7], &s1], 0)?;
+        w.push(next);
+    }
+
+    let mut v = state.to_vec();
+    for t in 0..64 {
+        let (a, b, c, d, e, f, g, h) = (&v[0], &v[1], &v[2], &v[3], &v[4], &v[5], &v[6], &v[7]);
+        let sigma1 = Word::xor3(cs, &e.rotr(6), &e.rotr(11), &e.rotr(25));
+        let ch = Word::ch(cs, e, f, g);
+        let sigma0 = Word::xor3(cs, &a.rotr(2), &a.rotr(13), &a.rotr(22));
+        let maj = Word::maj(cs, a, b, c);
+        // e' = d + T1 and a' = T1 + T2 are each reduced once, without reducing T1
+        let new_e = Word::add(cs, &[d, h, &sigma1, &ch, &w[t]], K[t])?;
+        let new_a = Word::add(cs, &[h, &sigma1, &ch, &w[t], &sigma0, &maj], K[t])?;
+        v = vec![new_a, a.clone(), b.clone(), c.clone(), new_e, e.clone(), f.clone(), g.clone()];
+    }
+
+    state.iter().zip(&v).map(|(s, x)| Word::add(cs, &[s, x], 0)).collect()
+}
+
+/// Hashes a `len`-byte message and returns the eight digest words. The prover passes
+/// the message, the verifier `None`. Message bits are allocated as witnesses, the
+/// padding (which depends only on `len`) is constant.
+pub fn sha256<CS: ConstraintSystem>(
+    cs: &mut CS,
+    message: Option<&[u8]>,
+    len: usize,
+) -> Result<Vec<Word>, R1CSError> {
+    if let Some(message) = message {
+        assert_eq!(message.len(), len, "message length does not match");
+    }
+
+    // Padded message, most significant bit of each byte first
+    let mut bits: Vec<(LinearCombination, Option<bool>)> = Vec::new();
+    for i in 0..len {
+        for j in (0..8).rev() {
+            let value = message.map(|m| (m[i] >> j) & 1 == 1);
+            bits.push((alloc_bit(cs, value)?.into(), value));
+        }
+    }
+    let padded_len = (len * 8 + 1 + 64).div_ceil(512) * 512;
+    let mut padding = vec![false; padded_len - len * 8];
+    padding[0] = true;
+    for (i, bit) in padding.iter_mut().rev().take(64).enumerate() {
+        *bit = ((len as u64 * 8) >> i) & 1 == 1;
+    }
+    bits.extend(
+        padding
+            .into_iter()
+            .map(|b| (LinearCombination::from(Scalar::from(b as u64)), Some(b))),
+    );
+
+    let mut state: Vec<Word> = IV.iter().map(|&v| Word::constant(v)).collect();
+    for block in bits.chunks(512) {
+        let words: Vec<Word> = block.chunks(32).map(Word::from_be_bits).collect();
+        state = compress(cs, &state, &words)?;
+    }
+    Ok(state)
+}
+
+/// Constrains `digest` to equal the public `expected` hash.
+pub fn enforce_digest<CS: ConstraintSystem>(cs: &mut CS, digest: &[Word], expected: &[u8; 32]) {
+    for (word, chunk) in digest.iter().zip(expected.chunks(4)) {
+        let expected = u32::from_be_bytes(chunk.try_into().unwrap());
+        for (i, bit) in word.bits.iter().enumerate() {
+            cs.constrain(bit.clone() - Scalar::from((expected >> i) as u64 & 1));
+        }
+    }
+}
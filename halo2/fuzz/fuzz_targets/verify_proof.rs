@@ -0,0 +1,35 @@
+//! Arbitrary bytes as a `Blake2bRead` transcript into `verify_proof` for the lookup range
+//! check with 8-bit limbs (k = 9). The verifier reads
+//! every commitment and evaluation from the transcript, so truncated or malformed bytes
+//! must come back as an error, never as a panic.
+#![no_main]
+
+use halo2_proofs::{
+    pasta::{EqAffine, Fp},
+    plonk::{keygen_vk, verify_proof, SingleVerifier, VerifyingKey},
+    poly::commitment::Params,
+    transcript::{Blake2bRead, Challenge255},
+};
+use halo2::range_check::LookupRangeProofCircuit;
+use libfuzzer_sys::fuzz_target;
+
+/// Same circuit, size and public input as the "lookup (8-bit limbs)" run of the
+/// range_proof example, so its saved proof verifies.
+const K: u32 = 9;
+const VALUE: u64 = 12345678;
+
+thread_local! {
+    static KEYS: (Params<EqAffine>, VerifyingKey<EqAffine>) = {
+        let params = Params::<EqAffine>::new(K);
+        let vk = keygen_vk(&params, &LookupRangeProofCircuit::<8>::default()).expect("keygen_vk should not fail");
+        (params, vk)
+    };
+}
+
+fuzz_target!(|data: &[u8]| {
+    KEYS.with(|(params, vk)| {
+        let instance = [Fp::from(VALUE)];
+        let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(data);
+        let _ = verify_proof(params, vk, SingleVerifier::new(params), &[&[&instance]], &mut transcript);
+    });
+});
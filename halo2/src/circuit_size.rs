@@ -0,0 +1,44 @@
+use std::fmt::Debug;
+
+use halo2_proofs::{
+    dev::{CircuitCost, MockProver},
+    pasta::{Eq, Fp},
+    plonk::{Circuit, ConstraintSystem, Error},
+};
+
+/// Largest `k` tried by `find_k` before giving up.
+pub const MAX_K: u32 = 24;
+
+/// Returns the smallest `k` for which `circuit` fits into `2^k` rows.
+///
+/// Runs the circuit's floor planner through `MockProver` for increasing `k`, starting
+/// from the minimum imposed by the constraint system (blinding rows etc.), until no
+/// `NotEnoughRowsAvailable` error is reported. Constraints are not checked here.
+pub fn find_k<C: Circuit<Fp>>(circuit: &C, instances: &[Vec<Fp>]) -> Result<u32, Error> {
+    let mut cs = ConstraintSystem::<Fp>::default();
+    C::configure(&mut cs);
+    let mut k = cs.minimum_rows().next_power_of_two().trailing_zeros();
+
+    loop {
+        match MockProver::run(k, circuit, instances.to_vec()) {
+            Ok(_) => return Ok(k),
+            Err(Error::NotEnoughRowsAvailable { .. }) | Err(Error::InstanceTooLarge) if k < MAX_K => k += 1,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Prints halo2's cost model estimate for `circuit` at `k`, without running keygen.
+///
+/// The estimate is printed through `CircuitCost`'s `Debug` impl, which requires the
+/// circuit to implement `Debug` as well.
+pub fn print_cost<C: Circuit<Fp> + Debug>(k: u32, circuit: &C) {
+    let cost = CircuitCost::<Eq, C>::measure(k, circuit);
+    let proof_size: usize = cost.proof_size(1).into();
+    let marginal_proof_size: usize = cost.marginal_proof_size().into();
+
+    println!("Minimal k: {} ({} rows)", k, 1u64 << k);
+    println!("Estimated proof size: {} bytes", proof_size);
+    println!("Marginal proof size per extra instance: {} bytes", marginal_proof_size);
+    println!("Circuit cost: {:?}", cost);
+}
@@ -0,0 +1,599 @@
+//! The `y = x^3 + x + 5` circuit of `cubic_expression`, built from an add chip and a
+//! mul chip. It is generic over the field, so `kzg` compiles this file a second time
+//! against the PSE fork of `halo2_proofs` and proves the same circuit over BN254.
+use std::marker::PhantomData;
+
+use group::ff::Field;
+use halo2_proofs::{
+    circuit::{AssignedCell, Chip, Layouter, Region, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Fixed, Instance, Selector},
+    poly::Rotation,
+};
+
+// ANCHOR: field-instructions
+/// A variable representing a number.
+#[derive(Clone)]
+struct Number<F: Field>(AssignedCell<F, F>);
+
+trait FieldInstructions<F: Field>: AddInstructions<F> + MulInstructions<F> {
+    /// Variable representing a number.
+    type Num;
+
+    /// Loads a number into the circuit as a private input.
+    fn load_private(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Value<F>,
+    ) -> Result<<Self as FieldInstructions<F>>::Num, Error>;
+
+    fn load_constant(&self, layouter: impl Layouter<F>, value: F) -> Result<<Self as FieldInstructions<F>>::Num, Error>;
+
+    /// Returns `y = x**3 + x + 5`.
+    fn cubic_expression(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        x: <Self as FieldInstructions<F>>::Num,
+    ) -> Result<<Self as FieldInstructions<F>>::Num, Error>;
+
+    /// Exposes a number as a public input to the circuit.
+    fn expose_public(
+        &self,
+        layouter: impl Layouter<F>,
+        num: <Self as FieldInstructions<F>>::Num,
+        row: usize,
+    ) -> Result<(), Error>;
+}
+// ANCHOR_END: field-instructions
+
+// ANCHOR: add-instructions
+trait AddInstructions<F: Field>: Chip<F> {
+    /// Variable representing a number.
+    type Num;
+
+    /// Returns `c = a + b`.
+    fn add(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error>;
+}
+// ANCHOR_END: add-instructions
+
+// ANCHOR: mul-instructions
+trait MulInstructions<F: Field>: Chip<F> {
+    /// Variable representing a number.
+    type Num;
+
+    /// Returns `c = a * b`.
+    fn mul(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error>;
+}
+// ANCHOR_END: mul-instructions
+
+// ANCHOR: field-config
+// The top-level config that provides all necessary columns and permutations
+// for the other configs.
+#[derive(Clone, Debug)]
+pub struct FieldConfig {
+    /// For this chip, we will use two advice columns to implement our instructions.
+    /// These are also the columns through which we communicate with other parts of
+    /// the circuit.
+    advice: [Column<Advice>; 2],
+
+    /// Public inputs
+    instance: Column<Instance>,
+
+    /// Constants, fixed at keygen so that the prover cannot choose them
+    constant: Column<Fixed>,
+
+    add_config: AddConfig,
+    mul_config: MulConfig,
+}
+// ANCHOR END: field-config
+
+// ANCHOR: add-config
+#[derive(Clone, Debug)]
+struct AddConfig {
+    advice: [Column<Advice>; 2],
+    s_add: Selector,
+}
+// ANCHOR_END: add-config
+
+// ANCHOR: mul-config
+#[derive(Clone, Debug)]
+struct MulConfig {
+    advice: [Column<Advice>; 2],
+    s_mul: Selector,
+}
+// ANCHOR END: mul-config
+
+// ANCHOR: field-chip
+/// The top-level chip that will implement the `FieldInstructions`.
+struct FieldChip<F: Field> {
+    config: FieldConfig,
+    _marker: PhantomData<F>,
+}
+// ANCHOR_END: field-chip
+
+// ANCHOR: add-chip
+struct AddChip<F: Field> {
+    config: AddConfig,
+    _marker: PhantomData<F>,
+}
+// ANCHOR END: add-chip
+
+// ANCHOR: mul-chip
+struct MulChip<F: Field> {
+    config: MulConfig,
+    _marker: PhantomData<F>,
+}
+// ANCHOR_END: mul-chip
+
+// ANCHOR: add-chip-trait-impl
+impl<F: Field> Chip<F> for AddChip<F> {
+    type Config = AddConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+// ANCHOR END: add-chip-trait-impl
+
+// ANCHOR: add-chip-impl
+impl<F: Field> AddChip<F> {
+    fn construct(config: <Self as Chip<F>>::Config, _loaded: <Self as Chip<F>>::Loaded) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 2],
+    ) -> <Self as Chip<F>>::Config {
+        let s_add = meta.selector();
+
+        // Define our addition gate!
+        meta.create_gate("add", |meta| {
+            let lhs = meta.query_advice(advice[0], Rotation::cur());
+            let rhs = meta.query_advice(advice[1], Rotation::cur());
+            let out = meta.query_advice(advice[0], Rotation::next());
+            let s_add = meta.query_selector(s_add);
+
+            vec![s_add * (lhs + rhs - out)]
+        });
+
+        AddConfig { advice, s_add }
+    }
+}
+// ANCHOR END: add-chip-impl
+
+// ANCHOR: add-instructions-impl
+impl<F: Field> AddInstructions<F> for FieldChip<F> {
+    type Num = Number<F>;
+    fn add(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error> {
+        let config = self.config().add_config.clone();
+
+        let add_chip = AddChip::<F>::construct(config, ());
+        add_chip.add(layouter, a, b)
+    }
+}
+
+impl<F: Field> AddInstructions<F> for AddChip<F> {
+    type Num = Number<F>;
+
+    fn add(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error> {
+        let config = self.config();
+
+        layouter.assign_region(
+            || "add",
+            |mut region: Region<'_, F>| {
+                // We only want to use a single addition gate in this region,
+                // so we enable it at region offset 0; this means it will constrain
+                // cells at offsets 0 and 1.
+                config.s_add.enable(&mut region, 0)?;
+
+                // The inputs we've been given could be located anywhere in the circuit,
+                // but we can only rely on relative offsets inside this region. So we
+                // assign new cells inside the region and constrain them to have the
+                // same values as the inputs.
+                a.0.copy_advice(|| "lhs", &mut region, config.advice[0], 0)?;
+                b.0.copy_advice(|| "rhs", &mut region, config.advice[1], 0)?;
+
+                // Now we can compute the addition result, which is to be assigned
+                // into the output position.
+                let value = a.0.value().copied() + b.0.value();
+
+                // Finally, we do the assignment to the output, returning a
+                // variable to be used in another part of the circuit.
+                region
+                    .assign_advice(|| "lhs + rhs", config.advice[0], 1, || value)
+                    .map(Number)
+            },
+        )
+    }
+}
+// ANCHOR END: add-instructions-impl
+
+// ANCHOR: mul-chip-trait-impl
+impl<F: Field> Chip<F> for MulChip<F> {
+    type Config = MulConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+// ANCHOR END: mul-chip-trait-impl
+
+// ANCHOR: mul-chip-impl
+impl<F: Field> MulChip<F> {
+    fn construct(config: <Self as Chip<F>>::Config, _loaded: <Self as Chip<F>>::Loaded) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 2],
+    ) -> <Self as Chip<F>>::Config {
+        for column in &advice {
+            meta.enable_equality(*column);
+        }
+        let s_mul = meta.selector();
+
+        // Define our multiplication gate!
+        meta.create_gate("mul", |meta| {
+            // To implement multiplication, we need three advice cells and a selector
+            // cell. We arrange them like so:
+            //
+            // | a0  | a1  | s_mul |
+            // |-----|-----|-------|
+            // | lhs | rhs | s_mul |
+            // | out |     |       |
+            //
+            // Gates may refer to any relative offsets we want, but each distinct
+            // offset adds a cost to the proof. The most common offsets are 0 (the
+            // current row), 1 (the next row), and -1 (the previous row), for which
+            // `Rotation` has specific constructors.
+            let lhs = meta.query_advice(advice[0], Rotation::cur());
+            let rhs = meta.query_advice(advice[1], Rotation::cur());
+            let out = meta.query_advice(advice[0], Rotation::next());
+            let s_mul = meta.query_selector(s_mul);
+
+            // The polynomial expression returned from `create_gate` will be
+            // constrained by the proving system to equal zero. Our expression
+            // has the following properties:
+            // - When s_mul = 0, any value is allowed in lhs, rhs, and out.
+            // - When s_mul != 0, this constrains lhs * rhs = out.
+            vec![s_mul * (lhs * rhs - out)]
+        });
+
+        MulConfig { advice, s_mul }
+    }
+}
+// ANCHOR_END: mul-chip-impl
+
+// ANCHOR: mul-instructions-impl
+impl<F: Field> MulInstructions<F> for FieldChip<F> {
+    type Num = Number<F>;
+    fn mul(
+        &self,
+        layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error> {
+        let config = self.config().mul_config.clone();
+        let mul_chip = MulChip::<F>::construct(config, ());
+        mul_chip.mul(layouter, a, b)
+    }
+}
+
+impl<F: Field> MulInstructions<F> for MulChip<F> {
+    type Num = Number<F>;
+
+    fn mul(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: Self::Num,
+        b: Self::Num,
+    ) -> Result<Self::Num, Error> {
+        let config = self.config();
+
+        layouter.assign_region(
+            || "mul",
+            |mut region: Region<'_, F>| {
+                // We only want to use a single multiplication gate in this region,
+                // so we enable it at region offset 0; this means it will constrain
+                // cells at offsets 0 and 1.
+                config.s_mul.enable(&mut region, 0)?;
+
+                // The inputs we've been given could be located anywhere in the circuit,
+                // but we can only rely on relative offsets inside this region. So we
+                // assign new cells inside the region and constrain them to have the
+                // same values as the inputs.
+                a.0.copy_advice(|| "lhs", &mut region, config.advice[0], 0)?;
+                b.0.copy_advice(|| "rhs", &mut region, config.advice[1], 0)?;
+
+                // Now we can compute the multiplication result, which is to be assigned
+                // into the output position.
+                let value = a.0.value().copied() * b.0.value();
+
+                // Finally, we do the assignment to the output, returning a
+                // variable to be used in another part of the circuit.
+                region
+                    .assign_advice(|| "lhs * rhs", config.advice[0], 1, || value)
+                    .map(Number)
+            },
+        )
+    }
+}
+// ANCHOR END: mul-instructions-impl
+
+// ANCHOR: field-chip-trait-impl
+impl<F: Field> Chip<F> for FieldChip<F> {
+    type Config = FieldConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+// ANCHOR_END: field-chip-trait-impl
+
+// ANCHOR: field-chip-impl
+impl<F: Field> FieldChip<F> {
+    fn construct(config: <Self as Chip<F>>::Config, _loaded: <Self as Chip<F>>::Loaded) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 2],
+        instance: Column<Instance>,
+        constant: Column<Fixed>,
+    ) -> <Self as Chip<F>>::Config {
+        let add_config = AddChip::configure(meta, advice);
+        let mul_config = MulChip::configure(meta, advice);
+
+        meta.enable_equality(instance);
+        meta.enable_equality(constant);
+
+        FieldConfig {
+            advice,
+            instance,
+            constant,
+            add_config,
+            mul_config,
+        }
+    }
+}
+// ANCHOR_END: field-chip-impl
+
+// ANCHOR: field-instructions-impl
+impl<F: Field> FieldInstructions<F> for FieldChip<F> {
+    type Num = Number<F>;
+
+    fn load_constant(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: F,
+    ) -> Result<<Self as FieldInstructions<F>>::Num, Error> {
+        let config = self.config();
+
+        layouter.assign_region(
+            || "load constant",
+            |mut region| {
+                // The fixed cell is part of the verifying key, and the equality
+                // constraint stops the prover from putting another value in the
+                // advice cell.
+                let fixed = region.assign_fixed(|| "constant", config.constant, 0, || Value::known(value))?;
+                let cell = region.assign_advice(|| "constant", config.advice[0], 0, || Value::known(value))?;
+                region.constrain_equal(cell.cell(), fixed.cell())?;
+
+                Ok(Number(cell))
+            },
+        )
+    }
+
+    fn load_private(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: Value<F>,
+    ) -> Result<<Self as FieldInstructions<F>>::Num, Error> {
+        let config = self.config();
+
+        layouter.assign_region(
+            || "load private",
+            |mut region| {
+                region
+                    .assign_advice(|| "private input", config.advice[0], 0, || value)
+                    .map(Number)
+            },
+        )
+    }
+
+    /// Returns `y = x**3 + x + 5`.
+    fn cubic_expression(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        x: <Self as FieldInstructions<F>>::Num,
+    ) -> Result<<Self as FieldInstructions<F>>::Num, Error> {
+        // Calculate x^2
+        let x_square = self.mul(layouter.namespace(|| "x * x"), x.clone(), x.clone())?;
+
+        // Calculate x^3
+        let x_cube = self.mul(layouter.namespace(|| "x^2 * x"), x_square, x.clone())?;
+
+        // Calculate x^3 + x
+        let x_cube_plus_x = self.add(layouter.namespace(|| "x^3 + x"), x_cube, x)?;
+        let five = F::ONE + F::ONE + F::ONE + F::ONE + F::ONE;
+        let constant_5 = self.load_constant(
+            layouter.namespace(|| "load constant 5"),
+            five
+        )?;
+        
+        // Calculate y = x^3 + x + 5
+        self.add(layouter.namespace(|| "x^3 + x + 5"), x_cube_plus_x, constant_5)
+    }
+
+    fn expose_public(
+        &self,
+        mut layouter: impl Layouter<F>,
+        num: <Self as FieldInstructions<F>>::Num,
+        row: usize,
+    ) -> Result<(), Error> {
+        let config = self.config();
+
+        layouter.constrain_instance(num.0.cell(), config.instance, row)
+    }
+}
+// ANCHOR_END: field-instructions-impl
+
+// ANCHOR: circuit
+/// The full circuit implementation.
+///
+/// In this struct we store the private input variables. We use `Value<F>` because
+/// they won't have any value during key generation. During proving, if any of these
+/// were `Value::unknown()` we would get an error.
+#[derive(Default, Debug)]
+pub struct MyCircuit<F: Field> {
+    pub x: Value<F>,
+}
+
+impl<F: Field> Circuit<F> for MyCircuit<F> {
+    type Config = FieldConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [meta.advice_column(), meta.advice_column()];
+        let instance = meta.instance_column();
+        let constant = meta.fixed_column();
+        FieldChip::configure(meta, advice, instance, constant)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let field_chip = FieldChip::<F>::construct(config, ());
+
+        // Load x as a private input.
+        let x = field_chip.load_private(layouter.namespace(|| "load x"), self.x)?;
+
+        // Compute y = x^3 + x + 5.
+        let y = field_chip.cubic_expression(&mut layouter, x)?;
+
+        // Expose the result y as a public input.
+        field_chip.expose_public(layouter.namespace(|| "expose y"), y, 0)
+    }
+}
+// ANCHOR_END: circuit
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::dev::MockProver;
+    use halo2curves::bn256::Fr;
+
+    /// `MyCircuit` with a prover that puts `forged` into the advice cell of the constant
+    /// 5. Unless `constrained` is set, the cell is loaded as `load_constant` loaded it
+    /// before it was tied to the fixed column.
+    struct ForgedConstant {
+        x: Fr,
+        forged: Fr,
+        constrained: bool,
+    }
+
+    impl Circuit<Fr> for ForgedConstant {
+        type Config = FieldConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { ..*self }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            MyCircuit::<Fr>::configure(meta)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fr>) -> Result<(), Error> {
+            let field_chip = FieldChip::<Fr>::construct(config.clone(), ());
+            let x = field_chip.load_private(layouter.namespace(|| "load x"), Value::known(self.x))?;
+            let x_square = field_chip.mul(layouter.namespace(|| "x * x"), x.clone(), x.clone())?;
+            let x_cube = field_chip.mul(layouter.namespace(|| "x^2 * x"), x_square, x.clone())?;
+            let x_cube_plus_x = field_chip.add(layouter.namespace(|| "x^3 + x"), x_cube, x)?;
+            let constant = layouter.assign_region(
+                || "load constant",
+                |mut region| {
+                    let fixed = region.assign_fixed(|| "constant", config.constant, 0, || Value::known(Fr::from(5)))?;
+                    let cell = region.assign_advice(|| "constant", config.advice[0], 0, || Value::known(self.forged))?;
+                    if self.constrained {
+                        region.constrain_equal(cell.cell(), fixed.cell())?;
+                    }
+                    Ok(Number(cell))
+                },
+            )?;
+            let y = field_chip.add(layouter.namespace(|| "x^3 + x + 5"), x_cube_plus_x, constant)?;
+            field_chip.expose_public(layouter.namespace(|| "expose y"), y, 0)
+        }
+    }
+
+    #[test]
+    fn test_forged_constant_is_rejected() {
+        let x = Fr::from(3);
+        // The output of a prover that adds 6 where the circuit says 5
+        let y = x * x * x + x + Fr::from(6);
+        let accepts = |constrained| {
+            let circuit = ForgedConstant {
+                x,
+                forged: Fr::from(6),
+                constrained,
+            };
+            // Enough rows for `MyCircuit`
+            MockProver::run(4, &circuit, vec![vec![y]]).unwrap().verify().is_ok()
+        };
+        // An advice cell alone does not pin the constant, so the forged output went through
+        assert!(accepts(false));
+        assert!(!accepts(true));
+    }
+}
@@ -0,0 +1,188 @@
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{
+        Advice, Circuit, Column, ConstraintSystem, Error, Expression, Instance, Selector,
+        create_proof, verify_proof, keygen_pk, keygen_vk, SingleVerifier,
+    },
+    pasta::{EqAffine, Fp},
+    poly::{commitment::Params, Rotation},
+    transcript::{Blake2bRead, Blake2bWrite, Challenge255},
+};
+use labkit::bench::{PhaseTimes, SizedRun};
+use labkit::rng::ExampleRng;
+use labkit::ladder;
+use labkit::memory;
+use group::ff::Field;
+use std::time::Instant;
+
+// Counts the heap usage that `memory::measure` reports
+#[global_allocator]
+static ALLOCATOR: labkit::memory::PeakAllocator = labkit::memory::PeakAllocator;
+
+/// Chains `x_{i+1} = x_i^3 + x_i + 5` for `steps` steps, one row per value, with
+/// `x_0` private and `x_steps` exposed as the public input.
+struct CubicLadderCircuit {
+    x0: Value<Fp>,
+    steps: usize,
+}
+
+#[derive(Clone)]
+struct CubicLadderConfig {
+    x: Column<Advice>,
+    selector: Selector,
+    instance: Column<Instance>,
+}
+
+/// Computes `x_steps` natively.
+fn ladder_output(x0: Fp, steps: usize) -> Fp {
+    (0..steps).fold(x0, |x, _| x.square() * x + x + Fp::from(ladder::STEP_CONSTANT))
+}
+
+impl Circuit<Fp> for CubicLadderCircuit {
+    type Config = CubicLadderConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            x0: Value::unknown(),
+            steps: self.steps,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        let x = meta.advice_column();
+        let selector = meta.selector();
+        let instance = meta.instance_column();
+        meta.enable_equality(x);
+        meta.enable_equality(instance);
+
+        // x_{i+1} = x_i^3 + x_i + 5, with x_i and x_{i+1} in consecutive rows
+        meta.create_gate("cubic step", |meta| {
+            let s = meta.query_selector(selector);
+            let x_cur = meta.query_advice(x, Rotation::cur());
+            let x_next = meta.query_advice(x, Rotation::next());
+            let five = Expression::Constant(Fp::from(ladder::STEP_CONSTANT));
+            vec![s * (x_next - (x_cur.clone() * x_cur.clone() * x_cur.clone() + x_cur + five))]
+        });
+
+        CubicLadderConfig { x, selector, instance }
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+        let output = layouter.assign_region(
+            || "cubic ladder",
+            |mut region| {
+                let mut x = self.x0;
+                let mut cell = region.assign_advice(|| "x_0", config.x, 0, || x)?;
+                for i in 0..self.steps {
+                    config.selector.enable(&mut region, i)?;
+                    x = x.map(|x| x.square() * x + x + Fp::from(ladder::STEP_CONSTANT));
+                    cell = region.assign_advice(|| format!("x_{}", i + 1), config.x, i + 1, || x)?;
+                }
+                Ok(cell)
+            },
+        )?;
+
+        layouter.constrain_instance(output.cell(), config.instance, 0)
+    }
+}
+
+fn main() {
+    let log_sizes = ladder::log_sizes_from_args();
+    ladder::sweep("cubic_ladder", "rows", &log_sizes, prove_and_verify);
+}
+
+/// Runs keygen, proving and verification for a ladder of `steps` steps.
+fn prove_and_verify(steps: usize) -> SizedRun {
+    let circuit = CubicLadderCircuit {
+        x0: Value::unknown(),
+        steps,
+    };
+
+    // One row per value plus the rows reserved for blinding
+    let mut cs = ConstraintSystem::<Fp>::default();
+    CubicLadderCircuit::configure(&mut cs);
+    let rows = steps + 1;
+    let k = (rows + cs.minimum_rows()).next_power_of_two().trailing_zeros();
+    println!("Rows used: {} (k = {})", rows, k);
+
+    let start0 = Instant::now();
+    let ((params, vk, pk), _) = memory::measure("Setup", || {
+        let params = Params::<EqAffine>::new(k);
+        let vk = keygen_vk(&params, &circuit).expect("keygen_vk should not fail");
+        let pk = keygen_pk(&params, vk.clone(), &circuit).expect("keygen_pk should not fail");
+        (params, vk, pk)
+    });
+    let setup_time = start0.elapsed();
+
+    // OS randomness, or a reproducible stream with `--seed <n>`
+    let mut rng = ExampleRng::from_args();
+    let x0 = Fp::random(&mut rng);
+    let instance = [ladder_output(x0, steps)];
+    let circuit = CubicLadderCircuit {
+        x0: Value::known(x0),
+        steps,
+    };
+
+    let start1 = Instant::now();
+    let (proof, prove_memory) = memory::measure("Prove", || {
+        let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+        create_proof(&params, &pk, &[circuit], &[&[&instance]], &mut rng, &mut transcript)
+            .expect("proof generation should not fail");
+        transcript.finalize()
+    });
+    let start2 = Instant::now();
+    println!("Proof size: {} bytes", proof.len());
+
+    let (result, _) = memory::measure("Verify", || {
+        let strategy = SingleVerifier::new(&params);
+        let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
+        verify_proof(&params, &vk, strategy, &[&[&instance]], &mut transcript)
+    });
+    let start3 = Instant::now();
+    result.expect("proof should verify");
+    println!("Proof verification successful!");
+
+    SizedRun {
+        size: rows,
+        times: PhaseTimes {
+            setup: setup_time,
+            prove: start2.duration_since(start1),
+            verify: start3.duration_since(start2),
+        },
+        proof_size: proof.len(),
+        prove_memory,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ladder_proves_its_output() {
+        let steps = ladder::steps(4);
+        let mut cs = ConstraintSystem::<Fp>::default();
+        CubicLadderCircuit::configure(&mut cs);
+        let k = (steps + 1 + cs.minimum_rows()).next_power_of_two().trailing_zeros();
+
+        let params = Params::<EqAffine>::new(k);
+        let empty = CubicLadderCircuit { x0: Value::unknown(), steps };
+        let vk = keygen_vk(&params, &empty).unwrap();
+        let pk = keygen_pk(&params, vk.clone(), &empty).unwrap();
+
+        let x0 = Fp::from(3);
+        let y = ladder_output(x0, steps);
+        let circuit = CubicLadderCircuit { x0: Value::known(x0), steps };
+        let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+        create_proof(&params, &pk, &[circuit], &[&[&[y]]], ExampleRng::new(Some(0)), &mut transcript).unwrap();
+        let proof = transcript.finalize();
+
+        let verify = |y: Fp| {
+            let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
+            verify_proof(&params, &vk, SingleVerifier::new(&params), &[&[&[y]]], &mut transcript).is_ok()
+        };
+        assert!(verify(y));
+        assert!(!verify(y + Fp::one()), "a wrong x_N should be rejected");
+    }
+}
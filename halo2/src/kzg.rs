@@ -0,0 +1,400 @@
+//! Proves the cubic and range circuits with three commitment schemes:
+//! IPA over Pasta, and KZG over BN254 with the GWC and SHPLONK multiopen arguments.
+//!
+//! The circuits are the ones of `cubic_expression` and `range_proof`, defined once in
+//! `src/cubic_circuit.rs` and `src/range_circuit.rs`. The IPA row proves them with the
+//! zcash `halo2_proofs` crate, like every other example. That crate only implements IPA,
+//! so the KZG rows use the PSE fork (`halo2_proofs_pse`): this binary renames the fork
+//! to `halo2_proofs` and compiles both circuit files a second time against it. `k` comes
+//! from `circuit_size::find_k` over Pasta; the layout does not depend on the field, so
+//! the KZG proofs use the same `k`.
+//!
+//! Not covered: the SHA-256 circuit, because `Table16Chip` from `halo2_gadgets` is
+//! implemented for `pallas::Base` only and cannot be instantiated over BN254, and the
+//! lookup-based range checks of `range_check.rs`, which use the zcash lookup API.
+extern crate halo2_proofs as zcash_halo2_proofs;
+extern crate halo2_proofs_pse as halo2_proofs;
+
+mod cubic_circuit;
+mod range_circuit;
+
+use std::time::Instant;
+
+use labkit::rng::ExampleRng;
+use halo2::circuit_size;
+use labkit::reference;
+use labkit::memory;
+use group::ff::{FromUniformBytes, WithSmallOrderMulGroup};
+use halo2_proofs::{
+    circuit::Value,
+    halo2curves::bn256::{Bn256, Fr},
+    plonk::{create_proof, keygen_pk, keygen_vk, verify_proof, Circuit, ProvingKey, VerifyingKey},
+    poly::{
+        commitment::{CommitmentScheme, ParamsProver, Prover, Verifier},
+        kzg::{
+            commitment::{KZGCommitmentScheme, ParamsKZG},
+            multiopen::{ProverGWC, ProverSHPLONK, VerifierGWC, VerifierSHPLONK},
+            strategy::SingleStrategy,
+        },
+        VerificationStrategy,
+    },
+    transcript::{
+        Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
+    },
+};
+use zcash_halo2_proofs::{
+    pasta::{EqAffine, Fp},
+    plonk::SingleVerifier,
+    poly::commitment::Params as ParamsIPA,
+};
+
+// Counts the heap usage that `memory::measure` reports
+#[global_allocator]
+static ALLOCATOR: labkit::memory::PeakAllocator = labkit::memory::PeakAllocator;
+
+/// Prove/verify time and proof size for one (circuit, scheme) pair.
+struct SchemeStats {
+    scheme: &'static str,
+    proof_size: usize,
+    prove_time: f64,
+    verify_time: f64,
+    verified: bool,
+}
+
+/// Creates an IPA proof of `circuit` for `instance` with the zcash crate, blinded with
+/// randomness from `rng`.
+fn ipa_prove<C: zcash_halo2_proofs::plonk::Circuit<Fp>>(
+    params: &ParamsIPA<EqAffine>,
+    pk: &zcash_halo2_proofs::plonk::ProvingKey<EqAffine>,
+    circuit: C,
+    instance: &[Fp],
+    rng: &mut ExampleRng,
+) -> Vec<u8> {
+    use zcash_halo2_proofs::transcript::{Blake2bWrite, Challenge255};
+
+    let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+    zcash_halo2_proofs::plonk::create_proof(params, pk, &[circuit], &[&[instance]], rng, &mut transcript)
+        .expect("proof generation should not fail");
+    transcript.finalize()
+}
+
+/// Checks an IPA `proof` against `instance` with the zcash crate.
+fn ipa_verify(
+    params: &ParamsIPA<EqAffine>,
+    vk: &zcash_halo2_proofs::plonk::VerifyingKey<EqAffine>,
+    proof: &[u8],
+    instance: &[Fp],
+) -> bool {
+    use zcash_halo2_proofs::transcript::{Blake2bRead, Challenge255};
+
+    let strategy = SingleVerifier::new(params);
+    let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(proof);
+    zcash_halo2_proofs::plonk::verify_proof(params, vk, strategy, &[&[instance]], &mut transcript).is_ok()
+}
+
+/// Runs keygen, proving and verification of `circuit` with IPA over Pasta.
+fn ipa_prove_and_verify<C: zcash_halo2_proofs::plonk::Circuit<Fp>>(
+    params: &ParamsIPA<EqAffine>,
+    circuit: C,
+    instance: &[Fp],
+    rng: &mut ExampleRng,
+) -> SchemeStats {
+    let scheme = "IPA (Pasta)";
+    println!("\n{}", scheme);
+    let (pk, _) = memory::measure("Setup", || {
+        let vk = zcash_halo2_proofs::plonk::keygen_vk(params, &circuit).expect("keygen_vk should not fail");
+        zcash_halo2_proofs::plonk::keygen_pk(params, vk, &circuit).expect("keygen_pk should not fail")
+    });
+
+    let start1 = Instant::now();
+    let (proof, _) = memory::measure("Prove", || ipa_prove(params, &pk, circuit, instance, rng));
+    let start2 = Instant::now();
+    let (verified, _) = memory::measure("Verify", || ipa_verify(params, pk.get_vk(), &proof, instance));
+    let start3 = Instant::now();
+
+    SchemeStats {
+        scheme,
+        proof_size: proof.len(),
+        prove_time: start2.duration_since(start1).as_secs_f64() * 1000.0,
+        verify_time: start3.duration_since(start2).as_secs_f64() * 1000.0,
+        verified,
+    }
+}
+
+/// Creates a proof of `circuit` for `instance` with the multiopen prover `P`, blinded
+/// with randomness from `rng`.
+fn prove<'params, Scheme, P, C>(
+    params: &'params Scheme::ParamsProver,
+    pk: &ProvingKey<Scheme::Curve>,
+    circuit: C,
+    instance: &[Scheme::Scalar],
+    rng: &mut ExampleRng,
+) -> Vec<u8>
+where
+    Scheme: CommitmentScheme,
+    Scheme::Scalar: Ord + WithSmallOrderMulGroup<3> + FromUniformBytes<64>,
+    P: Prover<'params, Scheme>,
+    C: Circuit<Scheme::Scalar>,
+{
+    let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+    create_proof::<Scheme, P, _, _, _, _>(params, pk, &[circuit], &[&[instance]], rng, &mut transcript)
+        .expect("proof generation should not fail");
+    transcript.finalize()
+}
+
+/// Checks `proof` against `instance` with the multiopen verifier `V` and verification
+/// strategy `Strategy`.
+fn verify<'params, Scheme, V, Strategy>(
+    params: &'params Scheme::ParamsVerifier,
+    vk: &VerifyingKey<Scheme::Curve>,
+    proof: &[u8],
+    instance: &[Scheme::Scalar],
+) -> bool
+where
+    Scheme: CommitmentScheme,
+    Scheme::Scalar: Ord + WithSmallOrderMulGroup<3> + FromUniformBytes<64>,
+    V: Verifier<'params, Scheme>,
+    Strategy: VerificationStrategy<'params, Scheme, V, Output = ()>,
+{
+    let strategy = Strategy::new(params);
+    let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(proof);
+    verify_proof::<Scheme, V, _, _, Strategy>(params, vk, strategy, &[&[instance]], &mut transcript).is_ok()
+}
+
+/// Runs keygen, proving and verification of `circuit` with KZG over BN254 and the
+/// multiopen prover `P` / verifier `V`.
+fn kzg_prove_and_verify<'params, P, V, C>(
+    scheme: &'static str,
+    params: &'params ParamsKZG<Bn256>,
+    circuit: C,
+    instance: &[Fr],
+    rng: &mut ExampleRng,
+) -> SchemeStats
+where
+    P: Prover<'params, KZGCommitmentScheme<Bn256>>,
+    V: Verifier<'params, KZGCommitmentScheme<Bn256>>,
+    SingleStrategy<'params, Bn256>: VerificationStrategy<'params, KZGCommitmentScheme<Bn256>, V, Output = ()>,
+    C: Circuit<Fr>,
+{
+    println!("\n{}", scheme);
+    let (pk, _) = memory::measure("Setup", || {
+        let vk = keygen_vk(params, &circuit).expect("keygen_vk should not fail");
+        keygen_pk(params, vk, &circuit).expect("keygen_pk should not fail")
+    });
+
+    let start1 = Instant::now();
+    let (proof, _) = memory::measure("Prove", || {
+        prove::<KZGCommitmentScheme<Bn256>, P, _>(params, &pk, circuit, instance, rng)
+    });
+    let start2 = Instant::now();
+
+    let verifier_params = params.verifier_params();
+    let (verified, _) = memory::measure("Verify", || {
+        verify::<KZGCommitmentScheme<Bn256>, V, SingleStrategy<_>>(verifier_params, pk.get_vk(), &proof, instance)
+    });
+    let start3 = Instant::now();
+
+    SchemeStats {
+        scheme,
+        proof_size: proof.len(),
+        prove_time: start2.duration_since(start1).as_secs_f64() * 1000.0,
+        verify_time: start3.duration_since(start2).as_secs_f64() * 1000.0,
+        verified,
+    }
+}
+
+/// Proves one circuit with IPA/Pasta, KZG-GWC/BN254 and KZG-SHPLONK/BN254.
+/// `pasta` and `bn254` build the circuit and its public input for each backend.
+fn compare_schemes<C: zcash_halo2_proofs::plonk::Circuit<Fp>, D: Circuit<Fr>>(
+    name: &str,
+    pasta: impl Fn() -> (C, Fp),
+    bn254: impl Fn() -> (D, Fr),
+    rng: &mut ExampleRng,
+) {
+    let (circuit, instance) = pasta();
+    let k = circuit_size::find_k(&circuit, &[vec![instance]]).expect("circuit should fit");
+    let ipa_params = ParamsIPA::<EqAffine>::new(k);
+    let kzg_params = ParamsKZG::<Bn256>::setup(k, &mut *rng);
+
+    let ipa = ipa_prove_and_verify(&ipa_params, circuit, &[instance], rng);
+    let (circuit, instance) = bn254();
+    let gwc = kzg_prove_and_verify::<ProverGWC<_>, VerifierGWC<_>, _>(
+        "KZG-GWC (BN254)",
+        &kzg_params,
+        circuit,
+        &[instance],
+        rng,
+    );
+    let (circuit, instance) = bn254();
+    let shplonk = kzg_prove_and_verify::<ProverSHPLONK<_>, VerifierSHPLONK<_>, _>(
+        "KZG-SHPLONK (BN254)",
+        &kzg_params,
+        circuit,
+        &[instance],
+        rng,
+    );
+
+    println!("\n{} (k = {})", name, k);
+    println!(
+        "{:<22} {:>12} {:>14} {:>15} {:>9}",
+        "scheme", "proof size", "prove time", "verify time", "verified"
+    );
+    for s in &[ipa, gwc, shplonk] {
+        println!(
+            "{:<22} {:>10} B {:>11.3} ms {:>12.3} ms {:>9}",
+            s.scheme, s.proof_size, s.prove_time, s.verify_time, s.verified
+        );
+    }
+}
+
+/// The cubic circuit for `x` over Pasta and its public output `y = x^3 + x + 5`.
+fn cubic_pasta(x: u64) -> (halo2::cubic_circuit::MyCircuit<Fp>, Fp) {
+    let circuit = halo2::cubic_circuit::MyCircuit {
+        x: zcash_halo2_proofs::circuit::Value::known(Fp::from(x)),
+    };
+    (circuit, Fp::from(reference::cubic(x)))
+}
+
+/// The cubic circuit for `x` over BN254 and its public output `y = x^3 + x + 5`.
+fn cubic_bn254(x: u64) -> (cubic_circuit::MyCircuit<Fr>, Fr) {
+    let circuit = cubic_circuit::MyCircuit {
+        x: Value::known(Fr::from(x)),
+    };
+    (circuit, Fr::from(reference::cubic(x)))
+}
+
+/// The range circuit for `value` over Pasta and its public input.
+fn range_pasta(value: u64) -> (halo2::range_circuit::RangeProofCircuit, Fp) {
+    let circuit = halo2::range_circuit::RangeProofCircuit {
+        input: zcash_halo2_proofs::circuit::Value::known(value),
+    };
+    (circuit, Fp::from(value))
+}
+
+/// The range circuit for `value` over BN254 and its public input.
+fn range_bn254(value: u64) -> (range_circuit::RangeProofCircuit, Fr) {
+    let circuit = range_circuit::RangeProofCircuit {
+        input: Value::known(value),
+    };
+    (circuit, Fr::from(value))
+}
+
+fn main() {
+    // OS randomness, or a reproducible stream with `--seed <n>`
+    let mut rng = ExampleRng::from_args();
+    compare_schemes("cubic_expression", || cubic_pasta(3), || cubic_bn254(3), &mut rng);
+    compare_schemes("range_proof", || range_pasta(12345678), || range_bn254(12345678), &mut rng);
+
+    println!("\nsha256: not proven with KZG, Table16Chip only supports pallas::Base (see `cargo run --bin sha256` for IPA).");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use labkit::hiding::{self, Hiding};
+    use labkit::reference::{CUBIC_X, RANGE_VALUE};
+    use labkit::tamper;
+
+    /// Proves `pasta(value)` and `pasta(value + 1)` with IPA and checks the first proof
+    /// against the tamper matrix, the second being swapped in.
+    fn assert_ipa_rejects_tampering<C: zcash_halo2_proofs::plonk::Circuit<Fp>>(
+        params: &ParamsIPA<EqAffine>,
+        pasta: impl Fn(u64) -> (C, Fp),
+        value: u64,
+    ) {
+        let (circuit, instance) = pasta(value);
+        let vk = zcash_halo2_proofs::plonk::keygen_vk(params, &circuit).unwrap();
+        let pk = zcash_halo2_proofs::plonk::keygen_pk(params, vk, &circuit).unwrap();
+        let mut rng = ExampleRng::new(None);
+        let proof = ipa_prove(params, &pk, circuit, &[instance], &mut rng);
+        let (circuit, other_instance) = pasta(value + 1);
+        let other = ipa_prove(params, &pk, circuit, &[other_instance], &mut rng);
+
+        tamper::assert_rejects_tampering(&proof, &other, |bytes| {
+            ipa_verify(params, pk.get_vk(), bytes, &[instance])
+        });
+    }
+
+    /// The same check with KZG and the multiopen prover `P` / verifier `V`.
+    fn assert_kzg_rejects_tampering<'params, P, V, C>(
+        params: &'params ParamsKZG<Bn256>,
+        bn254: impl Fn(u64) -> (C, Fr),
+        value: u64,
+    ) where
+        P: Prover<'params, KZGCommitmentScheme<Bn256>>,
+        V: Verifier<'params, KZGCommitmentScheme<Bn256>>,
+        SingleStrategy<'params, Bn256>: VerificationStrategy<'params, KZGCommitmentScheme<Bn256>, V, Output = ()>,
+        C: Circuit<Fr>,
+    {
+        let (circuit, instance) = bn254(value);
+        let vk = keygen_vk(params, &circuit).unwrap();
+        let pk = keygen_pk(params, vk, &circuit).unwrap();
+        let mut rng = ExampleRng::new(None);
+        let proof = prove::<KZGCommitmentScheme<Bn256>, P, _>(params, &pk, circuit, &[instance], &mut rng);
+        let (circuit, other_instance) = bn254(value + 1);
+        let other = prove::<KZGCommitmentScheme<Bn256>, P, _>(params, &pk, circuit, &[other_instance], &mut rng);
+
+        let verifier_params = params.verifier_params();
+        tamper::assert_rejects_tampering(&proof, &other, |bytes| {
+            verify::<KZGCommitmentScheme<Bn256>, V, SingleStrategy<_>>(verifier_params, pk.get_vk(), bytes, &[instance])
+        });
+    }
+
+    /// Runs the tamper checks with IPA, KZG-GWC and KZG-SHPLONK, at the `k` found over Pasta.
+    fn assert_all_schemes_reject_tampering<C: zcash_halo2_proofs::plonk::Circuit<Fp>, D: Circuit<Fr>>(
+        pasta: impl Fn(u64) -> (C, Fp),
+        bn254: impl Fn(u64) -> (D, Fr),
+        value: u64,
+    ) {
+        let (circuit, instance) = pasta(value);
+        let k = circuit_size::find_k(&circuit, &[vec![instance]]).unwrap();
+        let ipa_params = ParamsIPA::<EqAffine>::new(k);
+        let kzg_params = ParamsKZG::<Bn256>::setup(k, ExampleRng::new(None));
+        assert_ipa_rejects_tampering(&ipa_params, pasta, value);
+        assert_kzg_rejects_tampering::<ProverGWC<_>, VerifierGWC<_>, _>(&kzg_params, &bn254, value);
+        assert_kzg_rejects_tampering::<ProverSHPLONK<_>, VerifierSHPLONK<_>, _>(&kzg_params, &bn254, value);
+    }
+
+    #[test]
+    fn test_tampered_cubic_proofs_are_rejected() {
+        assert_all_schemes_reject_tampering(cubic_pasta, cubic_bn254, CUBIC_X);
+    }
+
+    #[test]
+    fn test_tampered_range_proofs_are_rejected() {
+        assert_all_schemes_reject_tampering(range_pasta, range_bn254, RANGE_VALUE);
+    }
+
+    /// Proves the cubic statement for `CUBIC_X` twice with KZG and the prover `P`.
+    fn kzg_cubic_hiding<'params, P>(params: &'params ParamsKZG<Bn256>) -> Hiding
+    where
+        P: Prover<'params, KZGCommitmentScheme<Bn256>>,
+    {
+        let (circuit, _) = cubic_bn254(CUBIC_X);
+        let vk = keygen_vk(params, &circuit).unwrap();
+        let pk = keygen_pk(params, vk, &circuit).unwrap();
+        hiding::check(|| {
+            let (circuit, instance) = cubic_bn254(CUBIC_X);
+            prove::<KZGCommitmentScheme<Bn256>, P, _>(params, &pk, circuit, &[instance], &mut ExampleRng::new(None))
+        })
+    }
+
+    #[test]
+    fn test_all_schemes_are_randomized() {
+        let (circuit, instance) = cubic_pasta(CUBIC_X);
+        let k = circuit_size::find_k(&circuit, &[vec![instance]]).unwrap();
+
+        let ipa_params = ParamsIPA::<EqAffine>::new(k);
+        let vk = zcash_halo2_proofs::plonk::keygen_vk(&ipa_params, &circuit).unwrap();
+        let pk = zcash_halo2_proofs::plonk::keygen_pk(&ipa_params, vk, &circuit).unwrap();
+        let ipa = hiding::check(|| {
+            let (circuit, instance) = cubic_pasta(CUBIC_X);
+            ipa_prove(&ipa_params, &pk, circuit, &[instance], &mut ExampleRng::new(None))
+        });
+        assert_eq!(ipa, Hiding::Randomized);
+
+        let kzg_params = ParamsKZG::<Bn256>::setup(k, ExampleRng::new(None));
+        assert_eq!(kzg_cubic_hiding::<ProverGWC<_>>(&kzg_params), Hiding::Randomized);
+        assert_eq!(kzg_cubic_hiding::<ProverSHPLONK<_>>(&kzg_params), Hiding::Randomized);
+    }
+}
@@ -0,0 +1,273 @@
+use std::collections::BTreeMap;
+
+use halo2_proofs::{
+    circuit::Value,
+    dev::CircuitLayout,
+    pasta::Fp,
+    plonk::{
+        Advice, Any, Assigned, Assignment, Circuit, Column, ConstraintSystem, Error, Fixed,
+        FloorPlanner, Instance, Selector,
+    },
+};
+use plotters::prelude::*;
+
+/// Cells assigned inside one region.
+struct RegionUsage {
+    name: String,
+    rows: Option<(usize, usize)>,
+    cells: usize,
+    selectors: usize,
+}
+
+/// An `Assignment` that records where the floor planner puts each region and cell,
+/// without storing any values.
+#[derive(Default)]
+struct UsageCollector {
+    regions: Vec<RegionUsage>,
+    current_region: Option<usize>,
+    /// Number of assigned cells per column
+    columns: BTreeMap<Column<Any>, usize>,
+    total_rows: usize,
+}
+
+impl UsageCollector {
+    fn record(&mut self, column: Column<Any>, row: usize) {
+        *self.columns.entry(column).or_default() += 1;
+        self.total_rows = self.total_rows.max(row + 1);
+
+        if let Some(region) = self.current_region.map(|i| &mut self.regions[i]) {
+            region.cells += 1;
+            region.rows = Some(match region.rows {
+                Some((start, end)) => (start.min(row), end.max(row)),
+                None => (row, row),
+            });
+        }
+    }
+}
+
+impl Assignment<Fp> for UsageCollector {
+    fn enter_region<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        self.current_region = Some(self.regions.len());
+        self.regions.push(RegionUsage {
+            name: name_fn().into(),
+            rows: None,
+            cells: 0,
+            selectors: 0,
+        });
+    }
+
+    fn exit_region(&mut self) {
+        self.current_region = None;
+    }
+
+    fn enable_selector<A, AR>(&mut self, _: A, _: &Selector, row: usize) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.total_rows = self.total_rows.max(row + 1);
+        if let Some(i) = self.current_region {
+            self.regions[i].selectors += 1;
+        }
+        Ok(())
+    }
+
+    fn query_instance(&self, _: Column<Instance>, _: usize) -> Result<Value<Fp>, Error> {
+        Ok(Value::unknown())
+    }
+
+    fn assign_advice<V, VR, A, AR>(&mut self, _: A, column: Column<Advice>, row: usize, _: V) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<Fp>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.record(column.into(), row);
+        Ok(())
+    }
+
+    fn assign_fixed<V, VR, A, AR>(&mut self, _: A, column: Column<Fixed>, row: usize, _: V) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<Fp>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.record(column.into(), row);
+        Ok(())
+    }
+
+    fn copy(&mut self, _: Column<Any>, _: usize, _: Column<Any>, _: usize) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn fill_from_row(&mut self, _: Column<Fixed>, _: usize, _: Value<Assigned<Fp>>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn push_namespace<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn pop_namespace(&mut self, _: Option<String>) {}
+}
+
+/// Position of `target` among the values `alloc` hands out on a fresh constraint system.
+///
+/// The zcash crate keeps column indices and the column counts of `ConstraintSystem`
+/// private, but its allocators number columns, selectors and lookups consecutively.
+fn allocation_index<T: PartialEq>(target: &T, alloc: impl Fn(&mut ConstraintSystem<Fp>) -> T) -> usize {
+    let mut fresh = ConstraintSystem::<Fp>::default();
+    (0..).find(|_| alloc(&mut fresh) == *target).unwrap()
+}
+
+/// Number of values `alloc` has already handed out on `cs`, i.e. the index of the next one.
+fn allocated<T: PartialEq>(cs: &ConstraintSystem<Fp>, alloc: impl Fn(&mut ConstraintSystem<Fp>) -> T) -> usize {
+    let next = alloc(&mut cs.clone());
+    allocation_index(&next, alloc)
+}
+
+/// Index of `column` within its column type.
+fn column_index(column: &Column<Any>) -> usize {
+    match column.column_type() {
+        Any::Advice => allocation_index(column, |cs| cs.advice_column().into()),
+        Any::Fixed => allocation_index(column, |cs| cs.fixed_column().into()),
+        Any::Instance => allocation_index(column, |cs| cs.instance_column().into()),
+    }
+}
+
+/// Columns, selectors and lookup arguments configured by a circuit.
+#[derive(Clone, Copy, Debug)]
+pub struct ColumnCounts {
+    pub advice: usize,
+    /// Fixed columns before keygen turns the selectors into fixed columns
+    pub fixed: usize,
+    pub instance: usize,
+    pub selectors: usize,
+    pub lookups: usize,
+}
+
+/// Counts the columns, selectors and lookups of a configured constraint system.
+pub fn column_counts(cs: &ConstraintSystem<Fp>) -> ColumnCounts {
+    ColumnCounts {
+        advice: allocated(cs, ConstraintSystem::advice_column),
+        fixed: allocated(cs, ConstraintSystem::fixed_column),
+        instance: allocated(cs, ConstraintSystem::instance_column),
+        selectors: allocated(cs, ConstraintSystem::selector),
+        // `lookup` returns the index of the argument it adds
+        lookups: allocated(cs, |cs| cs.lookup(|_| Vec::new())),
+    }
+}
+
+/// Runs the floor planner for `circuit` and records the resulting layout.
+///
+/// zcash's `ConstraintSystem` does not expose the columns registered with
+/// `enable_constant`, so the floor planner gets none and circuits measured here must
+/// not use `assign_advice_from_constant`.
+fn collect_usage<C: Circuit<Fp>>(circuit: &C) -> (ConstraintSystem<Fp>, UsageCollector) {
+    let mut cs = ConstraintSystem::<Fp>::default();
+    let config = C::configure(&mut cs);
+    let mut usage = UsageCollector::default();
+    C::FloorPlanner::synthesize(&mut usage, circuit, config, Vec::new())
+        .expect("synthesis should not fail");
+    (cs, usage)
+}
+
+/// Number of rows the floor planner uses for `circuit`, excluding blinding rows.
+pub fn rows_used<C: Circuit<Fp>>(circuit: &C) -> usize {
+    collect_usage(circuit).1.total_rows
+}
+
+/// Prints the regions laid out by the floor planner and how many rows of each column are used.
+pub fn print_summary<C: Circuit<Fp>>(k: u32, circuit: &C) {
+    let (cs, usage) = collect_usage(circuit);
+
+    let n = 1usize << k;
+    println!(
+        "Rows used: {} of {} (k = {}, {:.1}%)",
+        usage.total_rows,
+        n,
+        k,
+        usage.total_rows as f64 * 100.0 / n as f64
+    );
+    let counts = column_counts(&cs);
+    println!(
+        "Columns: {} advice, {} fixed, {} instance, {} selectors, {} lookups",
+        counts.advice, counts.fixed, counts.instance, counts.selectors, counts.lookups
+    );
+
+    // Regions with the same name (e.g. one per SHA-256 round) are merged
+    let mut regions: BTreeMap<&str, (usize, usize, usize, usize)> = BTreeMap::new();
+    for region in &usage.regions {
+        let entry = regions.entry(&region.name).or_default();
+        entry.0 += 1;
+        entry.1 += region.rows.map_or(0, |(start, end)| end - start + 1);
+        entry.2 += region.cells;
+        entry.3 += region.selectors;
+    }
+    println!("\n{:<40} {:>7} {:>9} {:>9} {:>10}", "region", "count", "rows", "cells", "selectors");
+    for (name, (count, rows, cells, selectors)) in &regions {
+        println!("{:<40} {:>7} {:>9} {:>9} {:>10}", name, count, rows, cells, selectors);
+    }
+
+    println!("\n{:<10} {:>6} {:>9} {:>12}", "column", "index", "cells", "utilisation");
+    for (column, cells) in &usage.columns {
+        println!(
+            "{:<10} {:>6} {:>9} {:>11.1}%",
+            format!("{:?}", column.column_type()),
+            column_index(column),
+            cells,
+            *cells as f64 * 100.0 / n as f64
+        );
+    }
+}
+
+/// Renders the circuit layout (the `dev-graph` view) to `<name>_layout.<format>`,
+/// where `format` is `png` or `svg`.
+pub fn render<C: Circuit<Fp>>(name: &str, k: u32, circuit: &C, format: &str) {
+    let path = format!("{}_layout.{}", name, format);
+    let title = format!("{} layout (k = {})", name, k);
+    match format {
+        "svg" => draw(k, circuit, SVGBackend::new(&path, (1024, 768)).into_drawing_area(), &title),
+        _ => draw(k, circuit, BitMapBackend::new(&path, (1024, 768)).into_drawing_area(), &title),
+    }
+    println!("Layout written to {}", path);
+}
+
+fn draw<C: Circuit<Fp>, DB: DrawingBackend>(
+    k: u32,
+    circuit: &C,
+    root: DrawingArea<DB, plotters::coord::Shift>,
+    title: &str,
+) {
+    root.fill(&WHITE).unwrap();
+    let root = root.titled(title, ("sans-serif", 30)).unwrap();
+    CircuitLayout::default()
+        .show_labels(false)
+        .render(k, circuit, &root)
+        .unwrap();
+    root.present().unwrap();
+}
+
+/// Handles `--layout [png|svg]`: prints the utilisation summary and renders the layout.
+/// Returns `false` if the flag was not given.
+pub fn from_args<C: Circuit<Fp>>(name: &str, k: u32, circuit: &C) -> bool {
+    let args: Vec<String> = std::env::args().collect();
+    let Some(pos) = args.iter().position(|a| a == "--layout") else {
+        return false;
+    };
+    let format = args.get(pos + 1).map(String::as_str).unwrap_or("png");
+
+    println!("\n== {} ==", name);
+    print_summary(k, circuit);
+    render(name, k, circuit, format);
+    true
+}
@@ -0,0 +1,5 @@
+pub mod circuit_size;
+pub mod cubic_circuit;
+pub mod layout;
+pub mod range_check;
+pub mod range_circuit;
@@ -0,0 +1,204 @@
+use std::marker::PhantomData;
+
+use group::ff::PrimeField;
+use halo2_proofs::{
+    circuit::{AssignedCell, Chip, Layouter, SimpleFloorPlanner, Value},
+    pasta::Fp,
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Expression, Instance, Selector, TableColumn},
+    poly::Rotation,
+};
+
+/// Bit width of the values checked by `RangeCheckChip`.
+pub const RANGE_BITS: usize = 32;
+
+/// Configuration for a lookup-based range check.
+///
+/// The value is decomposed into `LIMB_BITS`-bit limbs with a running sum
+/// laid out down a single advice column:
+///
+/// | z           | q_lookup | q_end |
+/// |-------------|----------|-------|
+/// | z_0 = value | 1        | 0     |
+/// | z_1         | 1        | 0     |
+/// | ...         | ...      | ...   |
+/// | z_n         | 0        | 1     |
+///
+/// where `z_{i+1} = (z_i - limb_i) / 2^LIMB_BITS`. Each `limb_i = z_i - 2^LIMB_BITS * z_{i+1}`
+/// is looked up in a fixed table of `0..2^LIMB_BITS`, and `z_n` is constrained to zero.
+#[derive(Clone, Debug)]
+pub struct RangeCheckConfig<const LIMB_BITS: usize> {
+    z: Column<Advice>,
+    q_lookup: Selector,
+    q_end: Selector,
+    table: TableColumn,
+}
+
+pub struct RangeCheckChip<F: PrimeField, const LIMB_BITS: usize> {
+    config: RangeCheckConfig<LIMB_BITS>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: PrimeField, const LIMB_BITS: usize> Chip<F> for RangeCheckChip<F, LIMB_BITS> {
+    type Config = RangeCheckConfig<LIMB_BITS>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: PrimeField, const LIMB_BITS: usize> RangeCheckChip<F, LIMB_BITS> {
+    /// Number of limbs needed to cover `RANGE_BITS`.
+    pub const NUM_LIMBS: usize = RANGE_BITS / LIMB_BITS;
+
+    pub fn construct(config: <Self as Chip<F>>::Config) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<F>, z: Column<Advice>) -> <Self as Chip<F>>::Config {
+        assert_eq!(RANGE_BITS % LIMB_BITS, 0, "limb size must divide the range");
+
+        let q_lookup = meta.complex_selector();
+        let q_end = meta.selector();
+        let table = meta.lookup_table_column();
+
+        meta.enable_equality(z);
+
+        // limb = z_cur - 2^LIMB_BITS * z_next must be in the table. When the selector
+        // is off the expression is zero, which is always in the table.
+        meta.lookup(|meta| {
+            let q_lookup = meta.query_selector(q_lookup);
+            let z_cur = meta.query_advice(z, Rotation::cur());
+            let z_next = meta.query_advice(z, Rotation::next());
+            let shift = Expression::Constant(F::from(1u64 << LIMB_BITS));
+
+            vec![(q_lookup * (z_cur - z_next * shift), table)]
+        });
+
+        // The running sum must end at zero, otherwise the value has more than RANGE_BITS bits.
+        meta.create_gate("running sum ends at zero", |meta| {
+            let q_end = meta.query_selector(q_end);
+            let z = meta.query_advice(z, Rotation::cur());
+
+            vec![q_end * z]
+        });
+
+        RangeCheckConfig {
+            z,
+            q_lookup,
+            q_end,
+            table,
+        }
+    }
+
+    /// Fills the fixed table with `0..2^LIMB_BITS`.
+    pub fn load_table(&self, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let config = self.config();
+
+        layouter.assign_table(
+            || "limb table",
+            |mut table| {
+                for i in 0..(1usize << LIMB_BITS) {
+                    table.assign_cell(
+                        || format!("limb {}", i),
+                        config.table,
+                        i,
+                        || Value::known(F::from(i as u64)),
+                    )?;
+                }
+                Ok(())
+            },
+        )
+    }
+
+    /// Range-checks `value` and returns the cell holding it (`z_0`).
+    pub fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: Value<u64>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let config = self.config();
+
+        layouter.assign_region(
+            || "range check",
+            |mut region| {
+                let mut z_0 = None;
+
+                for i in 0..=Self::NUM_LIMBS {
+                    // z_i = value >> (LIMB_BITS * i), computed over the integers
+                    let z_val = value.map(|v| {
+                        let shift = (LIMB_BITS * i) as u32;
+                        F::from(v.checked_shr(shift).unwrap_or(0))
+                    });
+                    let cell = region.assign_advice(|| format!("z_{}", i), config.z, i, || z_val)?;
+
+                    if i < Self::NUM_LIMBS {
+                        config.q_lookup.enable(&mut region, i)?;
+                    } else {
+                        config.q_end.enable(&mut region, i)?;
+                    }
+                    if i == 0 {
+                        z_0 = Some(cell);
+                    }
+                }
+
+                Ok(z_0.expect("at least one row is assigned"))
+            },
+        )
+    }
+}
+
+/// Range proof using `RangeCheckChip`: a running sum of `LIMB_BITS`-bit limbs,
+/// each checked against a lookup table instead of 32 parallel bit columns. The value
+/// is the circuit's only public input.
+#[derive(Default, Debug)]
+pub struct LookupRangeProofCircuit<const LIMB_BITS: usize> {
+    pub input: Value<u64>,
+}
+
+#[derive(Clone)]
+pub struct LookupRangeProofConfig<const LIMB_BITS: usize> {
+    range_check: RangeCheckConfig<LIMB_BITS>,
+    instance: Column<Instance>,
+}
+
+impl<const LIMB_BITS: usize> Circuit<Fp> for LookupRangeProofCircuit<LIMB_BITS> {
+    type Config = LookupRangeProofConfig<LIMB_BITS>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        let z = meta.advice_column();
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+
+        LookupRangeProofConfig {
+            range_check: RangeCheckChip::<Fp, LIMB_BITS>::configure(meta, z),
+            instance,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        let chip = RangeCheckChip::<Fp, LIMB_BITS>::construct(config.range_check);
+        chip.load_table(layouter.namespace(|| "load table"))?;
+
+        let input_cell = chip.assign(layouter.namespace(|| "range check"), self.input)?;
+
+        // Bind the witnessed input to the public instance value
+        layouter.constrain_instance(input_cell.cell(), config.instance, 0)
+    }
+}
@@ -0,0 +1,115 @@
+//! The bit-decomposition range check of `range_proof`: `input < 2^32` with one advice
+//! column per bit. It implements `Circuit` for every prime field, so `kzg` compiles this
+//! file a second time against the PSE fork of `halo2_proofs` and proves it over BN254.
+use group::ff::PrimeField;
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Expression, Instance, Selector},
+    poly::Rotation,
+};
+
+#[derive(Default, Debug)]
+pub struct RangeProofCircuit {
+    pub input: Value<u64>,
+}
+
+#[derive(Clone)]
+pub struct RangeProofConfig {
+    input: Column<Advice>,
+    bits: [Column<Advice>; 32],
+    selector: Selector,
+    /// Public input: the value being range-checked, copied from `input`
+    instance: Column<Instance>,
+}
+
+impl<F: PrimeField> Circuit<F> for RangeProofCircuit {
+    type Config = RangeProofConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let input = meta.advice_column();
+        let bits = [(); 32].map(|_| meta.advice_column());
+        let selector = meta.selector();
+        let instance = meta.instance_column();
+
+        meta.enable_equality(input);
+        meta.enable_equality(instance);
+        for bit in &bits {
+            meta.enable_equality(*bit);
+        }
+
+        meta.create_gate("Range Proof", |meta| {
+            let selector = meta.query_selector(selector);
+            let input = meta.query_advice(input, Rotation::cur());
+
+            let mut constraints = Vec::new();
+
+            for bit in bits.iter() {
+                let b = meta.query_advice(*bit, Rotation::cur());
+                constraints.push(selector.clone() * b.clone() * (b - Expression::Constant(F::ONE)));
+            }
+
+            let reconstructed_input = bits.iter().enumerate().fold(
+                Expression::Constant(F::ZERO),
+                |acc, (i, bit)| {
+                    acc + meta.query_advice(*bit, Rotation::cur()) * Expression::Constant(F::from(1 << i))
+                },
+            );
+
+            constraints.push(selector * (input - reconstructed_input));
+
+            constraints
+        });
+
+        RangeProofConfig {
+            input,
+            bits,
+            selector,
+            instance,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let input_cell = layouter.assign_region(
+            || "Range Proof",
+            |mut region| {
+                let input_val = self.input;
+                let input_cell = region.assign_advice(|| "input", config.input, 0, || input_val.map(F::from))?;
+    
+                // Use and_then and map methods
+                let current_val = self.input.and_then(Value::known);
+                let mut current = 0u64;
+                
+                // Use map to process value
+                current_val.map(|v| current = v);
+    
+                for i in 0..32 {
+                    let bit_val = current & 1;
+                    current >>= 1;
+    
+                    region.assign_advice(
+                        || format!("bit {}", i),
+                        config.bits[i],
+                        0,
+                        || Value::known(F::from(bit_val)),
+                    )?;
+                }
+    
+                config.selector.enable(&mut region, 0)?;
+    
+                Ok(input_cell)
+            },
+        )?;
+
+        // Bind the witnessed input to the public instance value
+        layouter.constrain_instance(input_cell.cell(), config.instance, 0)
+    }
+}
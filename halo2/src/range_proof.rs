@@ -1,163 +1,378 @@
 use halo2_proofs::{
-    circuit::{Layouter, SimpleFloorPlanner, Value},
+    circuit::Value,
     plonk::{
-        Circuit, ConstraintSystem, Error, Expression, Selector,
-        create_proof, verify_proof, keygen_pk, keygen_vk, SingleVerifier
+        Circuit, ConstraintSystem, Error,
+        ProvingKey, VerifyingKey, create_proof, verify_proof, keygen_pk, keygen_vk, SingleVerifier
     },
     pasta::{Fp, EqAffine},  // Add EqAffine
-    poly::{commitment::Params, Rotation},
+    poly::commitment::Params,
     transcript::{Blake2bWrite, Blake2bRead, Challenge255},
 };
-use rand_core::OsRng;  // Use rand_core instead of rand
-use std::time::Instant;
+use labkit::bench::{self, PhaseTimes};
+use labkit::input::{self, RangeInput};
+use halo2::range_circuit::RangeProofCircuit;
+use labkit::rng::ExampleRng;
+use halo2::circuit_size;
+use halo2::layout;
+use labkit::corpus;
+use labkit::scaling;
+use labkit::memory;
+use halo2::range_check::{self, LookupRangeProofCircuit};
+use std::fmt::Debug;
+use std::time::{Duration, Instant};
 
-#[derive(Default)]
-struct RangeProofCircuit {
-    input: Value<u64>,
+// Counts the heap usage that `memory::measure` reports
+#[global_allocator]
+static ALLOCATOR: labkit::memory::PeakAllocator = labkit::memory::PeakAllocator;
+
+/// Creates a proof that `circuit.input` is in range and equals `public_input`, blinded
+/// with randomness from `rng`.
+fn prove<C: Circuit<Fp>>(
+    params: &Params<EqAffine>,
+    pk: &ProvingKey<EqAffine>,
+    circuit: C,
+    public_input: u64,
+    rng: &mut ExampleRng,
+) -> Result<Vec<u8>, Error> {
+    let instance = [Fp::from(public_input)];
+    let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+    create_proof(
+        params,
+        pk,
+        &[circuit],
+        &[&[&instance]],
+        rng,
+        &mut transcript,
+    )?;
+    Ok(transcript.finalize())
 }
 
-#[derive(Clone)]
-struct RangeProofConfig {
-    input: halo2_proofs::plonk::Column<halo2_proofs::plonk::Advice>,
-    bits: [halo2_proofs::plonk::Column<halo2_proofs::plonk::Advice>; 32],
-    selector: Selector,
+/// Verifies a range proof against the claimed public value.
+fn verify(
+    params: &Params<EqAffine>,
+    vk: &VerifyingKey<EqAffine>,
+    proof: &[u8],
+    public_input: u64,
+) -> Result<(), Error> {
+    let instance = [Fp::from(public_input)];
+    let strategy = SingleVerifier::new(params);
+    let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(proof);
+    verify_proof(
+        params,
+        vk,
+        strategy,
+        &[&[&instance]],
+        &mut transcript,
+    )
 }
 
-impl Circuit<Fp> for RangeProofCircuit {
-    type Config = RangeProofConfig;
-    type FloorPlanner = SimpleFloorPlanner;
+/// Size of one range-check circuit, measured once before it is proven.
+struct CircuitShape {
+    k: u32,
+    /// Rows assigned by the floor planner, lookup table included
+    rows: usize,
+    columns: layout::ColumnCounts,
+}
+
+/// Finds the smallest `k` for `circuit`, prints halo2's cost estimate and measures
+/// the rows and columns it uses.
+fn measure_circuit<C: Circuit<Fp> + Debug>(name: &str, circuit: &C, value: u64) -> CircuitShape {
+    println!("\n{}", name);
+    let k = circuit_size::find_k(circuit, &[vec![Fp::from(value)]]).expect("circuit should fit");
+    circuit_size::print_cost(k, circuit);
 
-    fn without_witnesses(&self) -> Self {
-        Self::default()
+    let mut cs = ConstraintSystem::<Fp>::default();
+    C::configure(&mut cs);
+    CircuitShape {
+        k,
+        rows: layout::rows_used(circuit),
+        columns: layout::column_counts(&cs),
     }
+}
 
-    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
-        let input = meta.advice_column();
-        let bits = [(); 32].map(|_| meta.advice_column());
-        let selector = meta.selector();
+/// Measurements for one range-check circuit.
+struct RangeProofStats {
+    name: &'static str,
+    proof_size: usize,
+    setup_time: f64,
+    prove_time: f64,
+    verify_time: f64,
+}
 
-        meta.enable_equality(input);
-        for bit in &bits {
-            meta.enable_equality(*bit);
+impl RangeProofStats {
+    fn phase_times(&self) -> PhaseTimes {
+        PhaseTimes {
+            setup: Duration::from_secs_f64(self.setup_time / 1000.0),
+            prove: Duration::from_secs_f64(self.prove_time / 1000.0),
+            verify: Duration::from_secs_f64(self.verify_time / 1000.0),
         }
+    }
+}
+
+/// Runs keygen, proving and verification for `circuit` with `2^k` rows and collects
+/// its costs.
+fn bench_range_proof<C: Circuit<Fp>>(
+    name: &'static str,
+    k: u32,
+    circuit: C,
+    value: u64,
+) -> RangeProofStats {
+    // Parameter setup
+    let params = Params::<EqAffine>::new(k);
+
+    // Generate verification key and proving key
+    let start0 = Instant::now();
+    let ((vk, pk), _) = memory::measure("Setup", || {
+        let vk = keygen_vk(&params, &circuit).expect("keygen_vk should not fail");
+        let pk = keygen_pk(&params, vk.clone(), &circuit).expect("keygen_pk should not fail");
+        (vk, pk)
+    });
+    let setup_time = start0.elapsed();
 
-        meta.create_gate("Range Proof", |meta| {
-            let selector = meta.query_selector(selector);
-            let input = meta.query_advice(input, Rotation::cur());
+    // Generate proof, with OS randomness or a reproducible stream with `--seed <n>`
+    let mut rng = ExampleRng::from_args();
+    println!("Creating proof for {}...", name);
+    let start1 = Instant::now();
+    let (proof, _) = memory::measure("Prove", || {
+        prove(&params, &pk, circuit, value, &mut rng).expect("Proof generation should not fail")
+    });
+    let start2 = Instant::now();
+
+    // Verify proof
+    let (verify_result, _) = memory::measure("Verify", || verify(&params, &vk, &proof, value));
+    let start3 = Instant::now();
+    println!("Verification result: {:?}", verify_result);
 
-            let mut constraints = Vec::new();
+    // `--save-corpus <dir>`: seed the fuzz target with this proof, named after the chip
+    let seed_name: Vec<&str> = name.split(|c: char| !c.is_ascii_alphanumeric()).filter(|w| !w.is_empty()).collect();
+    corpus::save_from_args("verify_proof", &seed_name.join("_"), &proof);
 
-            for bit in bits.iter() {
-                let b = meta.query_advice(*bit, Rotation::cur());
-                constraints.push(selector.clone() * b.clone() * (b - Expression::Constant(Fp::one())));
-            }
+    RangeProofStats {
+        name,
+        proof_size: proof.len(),
+        setup_time: setup_time.as_secs_f64() * 1000.0,
+        prove_time: start2.duration_since(start1).as_secs_f64() * 1000.0,
+        verify_time: start3.duration_since(start2).as_secs_f64() * 1000.0,
+    }
+}
 
-            let reconstructed_input = bits.iter().enumerate().fold(
-                Expression::Constant(Fp::zero()),
-                |acc, (i, bit)| {
-                    acc + meta.query_advice(*bit, Rotation::cur()) * Expression::Constant(Fp::from(1 << i))
-                },
+fn main() {
+    // `--input <file>` replaces the value, in the schema of snarkjs/rangeproof_*/input.json
+    let value = match input::from_args::<RangeInput>() {
+        Some(input) if input.bits != range_check::RANGE_BITS => {
+            eprintln!(
+                "error: the range-check chips prove {}-bit ranges, the input asks for {} bits",
+                range_check::RANGE_BITS,
+                input.bits
             );
+            std::process::exit(1);
+        }
+        Some(input) => input.value,
+        None => 12345678,
+    };
+    println!("Public input: {}", value);
+
+    let bit_columns = || RangeProofCircuit {
+        input: Value::known(value),
+    };
+    let lookup8 = || LookupRangeProofCircuit::<8> {
+        input: Value::known(value),
+    };
+    let lookup16 = || LookupRangeProofCircuit::<16> {
+        input: Value::known(value),
+    };
 
-            constraints.push(selector * (input - reconstructed_input));
+    // k, rows and columns of each chip, measured once outside of any timing
+    let shapes = [
+        // 32 bit columns in a single row
+        measure_circuit("bit columns", &bit_columns(), value),
+        // 4 limbs of 8 bits; the 2^8-row table dominates the row count
+        measure_circuit("lookup (8-bit limbs)", &lookup8(), value),
+        // 2 limbs of 16 bits, table of 2^16 rows
+        measure_circuit("lookup (16-bit limbs)", &lookup16(), value),
+    ];
+    let [bit_columns_k, lookup8_k, lookup16_k] = shapes.each_ref().map(|shape| shape.k);
 
-            constraints
+    // `--layout [png|svg]`: render all three range-check circuits instead of proving
+    if std::env::args().any(|a| a == "--layout") {
+        layout::from_args("range_proof", bit_columns_k, &bit_columns());
+        layout::from_args("range_proof_lookup8", lookup8_k, &lookup8());
+        layout::from_args("range_proof_lookup16", lookup16_k, &lookup16());
+        return;
+    }
+
+    // `--bench [samples]`: repeated setup/prove/verify of each chip
+    if let Some(config) = bench::BenchConfig::from_args() {
+        bench::run("range_proof", &config, || {
+            bench_range_proof("bit columns", bit_columns_k, bit_columns(), value).phase_times()
+        });
+        bench::run("range_proof_lookup8", &config, || {
+            bench_range_proof("lookup (8-bit limbs)", lookup8_k, lookup8(), value).phase_times()
         });
+        bench::run("range_proof_lookup16", &config, || {
+            bench_range_proof("lookup (16-bit limbs)", lookup16_k, lookup16(), value).phase_times()
+        });
+        return;
+    }
 
-        RangeProofConfig {
-            input,
-            bits,
-            selector,
-        }
+    // `--threads [1,2,4,...]`: prove time of each chip per rayon pool size
+    if let Some(counts) = scaling::thread_counts_from_args() {
+        let to_duration = |s: RangeProofStats| s.phase_times().prove;
+        scaling::sweep("range_proof (bit columns)", &counts, || {
+            to_duration(bench_range_proof("bit columns", bit_columns_k, bit_columns(), value))
+        });
+        scaling::sweep("range_proof (lookup, 8-bit limbs)", &counts, || {
+            to_duration(bench_range_proof("lookup (8-bit limbs)", lookup8_k, lookup8(), value))
+        });
+        scaling::sweep("range_proof (lookup, 16-bit limbs)", &counts, || {
+            to_duration(bench_range_proof("lookup (16-bit limbs)", lookup16_k, lookup16(), value))
+        });
+        return;
     }
 
-    fn synthesize(
-        &self,
-        config: Self::Config,
-        mut layouter: impl Layouter<Fp>,
-    ) -> Result<(), Error> {
-        layouter.assign_region(
-            || "Range Proof",
-            |mut region| {
-                let input_val = self.input;
-                region.assign_advice(|| "input", config.input, 0, || input_val.map(Fp::from))?;
-    
-                // Use and_then and map methods
-                let current_val = self.input.and_then(Value::known);
-                let mut current = 0u64;
-                
-                // Use map to process value
-                current_val.map(|v| current = v);
-    
-                for i in 0..32 {
-                    let bit_val = current & 1;
-                    current >>= 1;
-    
-                    region.assign_advice(
-                        || format!("bit {}", i),
-                        config.bits[i],
-                        0,
-                        || Value::known(Fp::from(bit_val as u64)),
-                    )?;
-                }
-    
-                config.selector.enable(&mut region, 0)?;
-    
-                Ok(())
-            },
-        )
+    let stats = [
+        bench_range_proof("bit columns", bit_columns_k, bit_columns(), value),
+        bench_range_proof("lookup (8-bit limbs)", lookup8_k, lookup8(), value),
+        bench_range_proof("lookup (16-bit limbs)", lookup16_k, lookup16(), value),
+    ];
+
+    // Output results
+    println!(
+        "\n{:<22} {:>3} {:>7} {:>7} {:>6} {:>8} {:>11} {:>14} {:>15}",
+        "chip", "k", "rows", "advice", "fixed", "lookups", "proof size", "prove time", "verify time"
+    );
+    for (s, shape) in stats.iter().zip(&shapes) {
+        println!(
+            "{:<22} {:>3} {:>7} {:>7} {:>6} {:>8} {:>9} B {:>11.3} ms {:>12.3} ms",
+            s.name,
+            shape.k,
+            shape.rows,
+            shape.columns.advice,
+            shape.columns.fixed,
+            shape.columns.lookups,
+            s.proof_size,
+            s.prove_time,
+            s.verify_time
+        );
     }
 }
 
-fn main() {
-    // Parameter setup
-    let k = 12;
-    let params = Params::<EqAffine>::new(k);
-    
-    // Create circuit instance
-    let circuit = RangeProofCircuit {
-        input: Value::known(12345678),
-    };
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use labkit::reference;
+    use labkit::tamper;
 
-    // Generate verification key and proving key
-    let vk = keygen_vk(&params, &circuit).expect("keygen_vk should not fail");
-    let pk = keygen_pk(&params, vk.clone(), &circuit).expect("keygen_pk should not fail");
+    fn setup(k: u32) -> (Params<EqAffine>, ProvingKey<EqAffine>) {
+        let params = Params::<EqAffine>::new(k);
+        let empty = RangeProofCircuit::default();
+        let vk = keygen_vk(&params, &empty).unwrap();
+        let pk = keygen_pk(&params, vk, &empty).unwrap();
+        (params, pk)
+    }
 
-    // Generate proof
-    let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
-    println!("Creating proof...");
-    let start1 = Instant::now();
-    create_proof(
-        &params,
-        &pk,
-        &[circuit],
-        &[&[]],
-        OsRng,
-        &mut transcript,
-    ).expect("Proof generation should not fail");
-    let start2 = Instant::now();
-    let proof = transcript.finalize();
+    #[test]
+    fn test_range_proof_public_input() {
+        let (params, pk) = setup(12);
+        let value = 12345678;
+        let circuit = RangeProofCircuit {
+            input: Value::known(value),
+        };
+        let proof = prove(&params, &pk, circuit, value, &mut ExampleRng::new(None)).unwrap();
 
-    // Verify proof
-    let strategy = SingleVerifier::new(&params);  // Add verification strategy
-    let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
-    let verify_result = verify_proof(
-        &params,
-        &vk,
-        strategy,  // Use verification strategy
-        &[&[]],
-        &mut transcript,  // Modify parameter order
-    );
-    let start3 = Instant::now();
+        assert!(verify(&params, pk.get_vk(), &proof, value).is_ok());
+        // A proof for one value must not verify against a different claimed value
+        assert!(verify(&params, pk.get_vk(), &proof, value + 1).is_err());
+    }
 
-    // Calculate time and size
-    let prove_time = start2.duration_since(start1).as_secs_f64() * 1000.0;
-    let verify_time = start3.duration_since(start2).as_secs_f64() * 1000.0;
-    let proof_size = proof.len();
+    #[test]
+    fn test_lookup_range_check() {
+        use halo2_proofs::dev::MockProver;
 
-    // Output results
-    println!("Prove time: {:.3} ms", prove_time);
-    println!("Verify time: {:.3} ms", verify_time);
-    println!("Proof size: {} bytes", proof_size);
-    println!("Verification result: {:?}", verify_result);
-}
\ No newline at end of file
+        let value = 12345678;
+        let circuit = LookupRangeProofCircuit::<8> {
+            input: Value::known(value),
+        };
+        let prover = MockProver::run(9, &circuit, vec![vec![Fp::from(value)]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // Values of 2^32 or more leave a non-zero running sum
+        let too_large = 1u64 << 32;
+        let circuit = LookupRangeProofCircuit::<8> {
+            input: Value::known(too_large),
+        };
+        let prover = MockProver::run(9, &circuit, vec![vec![Fp::from(too_large)]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    /// Whether the bit-column circuit and both lookup circuits accept `value` as the
+    /// public input.
+    fn accepted_by_each_chip(value: u64) -> [bool; 3] {
+        use halo2_proofs::dev::MockProver;
+
+        let instances = vec![vec![Fp::from(value)]];
+        let input = Value::known(value);
+        [
+            MockProver::run(12, &RangeProofCircuit { input }, instances.clone()),
+            MockProver::run(9, &LookupRangeProofCircuit::<8> { input }, instances.clone()),
+            MockProver::run(17, &LookupRangeProofCircuit::<16> { input }, instances),
+        ]
+        .map(|prover| prover.unwrap().verify().is_ok())
+    }
+
+    #[test]
+    fn test_reference_value_is_accepted() {
+        assert_eq!(reference::RANGE_BITS, 32);
+        assert_eq!(accepted_by_each_chip(reference::RANGE_VALUE), [true; 3]);
+    }
+
+    #[test]
+    fn test_out_of_range_values_are_rejected() {
+        for value in reference::OUT_OF_RANGE_VALUES {
+            assert_eq!(accepted_by_each_chip(value), [false; 3], "{} should be rejected", value);
+        }
+    }
+
+    #[test]
+    fn test_rows_include_the_lookup_table() {
+        let input = Value::known(reference::RANGE_VALUE);
+        assert_eq!(layout::rows_used(&RangeProofCircuit { input }), 1);
+        assert!(layout::rows_used(&LookupRangeProofCircuit::<8> { input }) >= 1 << 8);
+        assert!(layout::rows_used(&LookupRangeProofCircuit::<16> { input }) >= 1 << 16);
+    }
+
+    #[test]
+    fn test_find_k() {
+        let value = 12345678;
+        let instances = [vec![Fp::from(value)]];
+
+        // The 2^8-row lookup table plus blinding rows does not fit into 2^8 rows
+        let circuit = LookupRangeProofCircuit::<8> {
+            input: Value::known(value),
+        };
+        assert_eq!(circuit_size::find_k(&circuit, &instances).unwrap(), 9);
+
+        let circuit = LookupRangeProofCircuit::<16> {
+            input: Value::known(value),
+        };
+        assert_eq!(circuit_size::find_k(&circuit, &instances).unwrap(), 17);
+    }
+
+    #[test]
+    fn test_tampered_proofs_are_rejected() {
+        let (params, pk) = setup(12);
+        let mut rng = ExampleRng::new(None);
+        let mut prove_value = |value: u64| {
+            let circuit = RangeProofCircuit {
+                input: Value::known(value),
+            };
+            prove(&params, &pk, circuit, value, &mut rng).unwrap()
+        };
+        let proof = prove_value(reference::RANGE_VALUE);
+        let other = prove_value(reference::RANGE_VALUE + 1);
+
+        tamper::assert_rejects_tampering(&proof, &other, |bytes| {
+            verify(&params, pk.get_vk(), bytes, reference::RANGE_VALUE).is_ok()
+        });
+    }
+}
@@ -3,11 +3,30 @@ use halo2_proofs::{
     pasta::{Fp, EqAffine},
     plonk::{Circuit, ConstraintSystem, Error},
 };
-use halo2_gadgets::sha256::{Sha256, Table16Chip, Table16Config, BlockWord};
-use rand_core::OsRng;
+use labkit::bench::{self, PhaseTimes};
+use labkit::input::{self, Sha256Input};
+use labkit::rng::ExampleRng;
+use halo2::circuit_size;
+use halo2::layout;
+use labkit::scaling;
+use labkit::memory;
+use labkit::bench::SizedRun;
+use halo2_gadgets::sha256::{BlockWord, Sha256Instructions, Table16Chip, Table16Config};
 use std::time::Instant;
 
-#[derive(Default)]
+// Counts the heap usage that `memory::measure` reports
+#[global_allocator]
+static ALLOCATOR: labkit::memory::PeakAllocator = labkit::memory::PeakAllocator;
+
+/// 32-bit words in a SHA-256 digest (`halo2_gadgets` keeps its `DIGEST_SIZE` private).
+const DIGEST_SIZE: usize = 8;
+
+/// The longest message that pads to one block. Without the feed-forward (see
+/// `Sha256Circuit::hash`) longer messages chain into a different function than
+/// SHA-256, so the example refuses them.
+const MAX_MESSAGE_LEN: usize = 55;
+
+#[derive(Default, Debug)]
 struct Sha256Circuit {
     pub input: Vec<u8>,
 }
@@ -17,7 +36,10 @@ impl Circuit<Fp> for Sha256Circuit {
     type FloorPlanner = SimpleFloorPlanner;
 
     fn without_witnesses(&self) -> Self {
-        Self::default()
+        // The number of blocks depends on the input length, so keep the length
+        Self {
+            input: vec![0; self.input.len()],
+        }
     }
 
     fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
@@ -29,6 +51,22 @@ impl Circuit<Fp> for Sha256Circuit {
         config: Self::Config,
         mut layouter: impl Layouter<Fp>,
     ) -> Result<(), Error> {
+        self.hash(config, &mut layouter)?;
+        Ok(())
+    }
+}
+
+impl Sha256Circuit {
+    /// Pads the input, compresses it block by block and returns the digest words.
+    /// `Table16Chip` leaves out the feed-forward of SHA-256: neither its `digest` nor
+    /// its `initialization` adds the chaining value to the compressed state. The words
+    /// are therefore the state after the 64 rounds of the last block, and for messages
+    /// of more than one block the chaining differs from SHA-256 as well.
+    fn hash(
+        &self,
+        config: Table16Config,
+        layouter: &mut impl Layouter<Fp>,
+    ) -> Result<[BlockWord; DIGEST_SIZE], Error> {
         let table16_chip = Table16Chip::construct(config.clone());
 
         Table16Chip::load(config, &mut layouter.namespace(|| "load_table"))?;
@@ -39,45 +77,43 @@ impl Circuit<Fp> for Sha256Circuit {
         let len_bits = len * 8;
         
         input_bytes.push(0x80);
-        while (input_bytes.len() + 8) % 64 != 0 {
+        while !(input_bytes.len() + 8).is_multiple_of(64) {
             input_bytes.push(0);
         }
         input_bytes.extend_from_slice(&((len_bits as u64).to_be_bytes()));
 
         println!("Padded input length: {} bytes", input_bytes.len());
 
-        let mut input_blocks = Vec::new();
-        for chunk in input_bytes.chunks(4) {
-            let mut buf = [0u8; 4];
-            buf[..chunk.len()].copy_from_slice(chunk);
-            input_blocks.push(BlockWord(Value::known(u32::from_be_bytes(buf))));
-        }
-
-        let mut block = [BlockWord(Value::known(0u32)); 16];
-        for (i, word) in input_blocks.iter().take(16).enumerate() {
-            block[i] = *word;
+        // The message is padded above, so the blocks are compressed directly rather
+        // than through `Sha256::update`/`finalize`, which would pad a second time.
+        let mut state = table16_chip.initialization_vector(layouter)?;
+        for (i, chunk) in input_bytes.chunks(64).enumerate() {
+            let mut block = [BlockWord(Value::known(0u32)); 16];
+            for (word, bytes) in block.iter_mut().zip(chunk.chunks(4)) {
+                *word = BlockWord(Value::known(u32::from_be_bytes(bytes.try_into().unwrap())));
+            }
+            if i > 0 {
+                state = table16_chip.initialization(layouter, &state)?;
+            }
+            state = table16_chip.compress(layouter, &state, block)?;
         }
 
-        let mut sha256 = Sha256::new(
-            table16_chip,
-            layouter.namespace(|| "sha256"),
-        )?;
-
-        sha256.update(
-            layouter.namespace(|| "update"),
-            &block,
-        )?;
-
-        let _digest = sha256.finalize(layouter.namespace(|| "finalize"))?;
-
-        Ok(())
+        table16_chip.digest(layouter, &state)
     }
 }
 
 fn main() {
-    let k = 17;
-
-    let message = b"Hello, ZK!";
+    // `--input <file>` replaces the message, in the schema of snarkjs/sha256_*/input.json
+    let message = input::from_args::<Sha256Input>().map_or(b"Hello, ZK!".to_vec(), |input| input.message);
+    let message = message.as_slice();
+    if message.len() > MAX_MESSAGE_LEN {
+        eprintln!(
+            "error: the message is {} bytes, but Table16Chip computes SHA-256 for one block of at most {} bytes",
+            message.len(),
+            MAX_MESSAGE_LEN
+        );
+        std::process::exit(1);
+    }
     println!("Input message: {:?}", String::from_utf8_lossy(message));
     println!("Message length: {} bytes", message.len());
 
@@ -85,51 +121,104 @@ fn main() {
         input: message.to_vec(),
     };
 
+    // Find the smallest usable k and report the cost estimate
+    let k = circuit_size::find_k(&circuit, &[]).expect("circuit should fit");
+    circuit_size::print_cost(k, &circuit);
+    if layout::from_args("sha256", k, &circuit) {
+        return;
+    }
+
+    if let Some(config) = bench::BenchConfig::from_args() {
+        bench::run("sha256", &config, || prove_and_verify(k, message).times);
+    } else if let Some(lengths) = scaling::input_lengths_from_args() {
+        let (lengths, longer): (Vec<usize>, Vec<usize>) = lengths.into_iter().partition(|&len| len <= MAX_MESSAGE_LEN);
+        if !longer.is_empty() {
+            println!(
+                "Skipping {:?} bytes: only one-block messages (at most {} bytes) hash to SHA-256 here",
+                longer, MAX_MESSAGE_LEN
+            );
+        }
+        scaling::input_size_sweep("sha256", "rows", &lengths, |len| {
+            let message = scaling::sample_message(len);
+            let circuit = Sha256Circuit {
+                input: message.clone(),
+            };
+            let k = circuit_size::find_k(&circuit, &[]).expect("circuit should fit");
+            prove_and_verify(k, &message)
+        });
+    } else if let Some(counts) = scaling::thread_counts_from_args() {
+        scaling::sweep("sha256", &counts, || prove_and_verify(k, message).times.prove);
+    } else {
+        prove_and_verify(k, message);
+    }
+}
+
+/// Runs setup, proving and verification of the SHA-256 circuit for `message` and
+/// returns the time of each phase, the rows used, the proof size and prover memory.
+fn prove_and_verify(k: u32, message: &[u8]) -> SizedRun {
+    let circuit = Sha256Circuit {
+        input: message.to_vec(),
+    };
+    let rows = layout::rows_used(&circuit);
+    println!("Rows used: {} of {}", rows, 1u64 << k);
+
     println!("Creating parameters with k = {}...", k);
+    let setup_start = Instant::now();
     let start = Instant::now();
     let params = halo2_proofs::poly::commitment::Params::<EqAffine>::new(k);
     println!("Parameters created in: {:?}", start.elapsed());
 
     println!("Generating verification key...");
     let start = Instant::now();
-    let vk = halo2_proofs::plonk::keygen_vk(&params, &circuit).expect("keygen_vk failed");
+    let (vk, _) = memory::measure("Setup (vk)", || {
+        halo2_proofs::plonk::keygen_vk(&params, &circuit).expect("keygen_vk failed")
+    });
     println!("Verification key generated in: {:?}", start.elapsed());
     
     println!("Generating proving key...");
     let start = Instant::now();
-    let pk = halo2_proofs::plonk::keygen_pk(&params, vk.clone(), &circuit).expect("keygen_pk failed");
+    let (pk, _) = memory::measure("Setup (pk)", || {
+        halo2_proofs::plonk::keygen_pk(&params, vk.clone(), &circuit).expect("keygen_pk failed")
+    });
     println!("Proving key generated in: {:?}", start.elapsed());
+    let setup_time = setup_start.elapsed();
 
+    // OS randomness, or a reproducible stream with `--seed <n>`
+    let mut rng = ExampleRng::from_args();
     println!("Creating proof...");
     let start = Instant::now();
-    let mut transcript = halo2_proofs::transcript::Blake2bWrite::<_, _, halo2_proofs::transcript::Challenge255<_>>::init(vec![]);
-    
-    halo2_proofs::plonk::create_proof(
-        &params,
-        &pk,
-        &[circuit],
-        &[&[]],
-        OsRng,
-        &mut transcript,
-    ).expect("proof generation failed");
-
-    let proof = transcript.finalize();
+    let (proof, prove_memory) = memory::measure("Prove", || {
+        let mut transcript = halo2_proofs::transcript::Blake2bWrite::<_, _, halo2_proofs::transcript::Challenge255<_>>::init(vec![]);
+
+        halo2_proofs::plonk::create_proof(
+            &params,
+            &pk,
+            &[circuit],
+            &[&[]],
+            &mut rng,
+            &mut transcript,
+        ).expect("proof generation failed");
+
+        transcript.finalize()
+    });
     let proof_time = start.elapsed();
     println!("Proof created in: {:?}", proof_time);
     println!("Proof size: {} bytes", proof.len());
 
     println!("Verifying proof...");
     let start = Instant::now();
-    let mut transcript = halo2_proofs::transcript::Blake2bRead::<_, _, halo2_proofs::transcript::Challenge255<_>>::init(&proof[..]);
-    let strategy = halo2_proofs::plonk::SingleVerifier::new(&params);
-    
-    let result = halo2_proofs::plonk::verify_proof(
-        &params,
-        &vk,
-        strategy,
-        &[&[]], 
-        &mut transcript,
-    );
+    let (result, _) = memory::measure("Verify", || {
+        let mut transcript = halo2_proofs::transcript::Blake2bRead::<_, _, halo2_proofs::transcript::Challenge255<_>>::init(&proof[..]);
+        let strategy = halo2_proofs::plonk::SingleVerifier::new(&params);
+
+        halo2_proofs::plonk::verify_proof(
+            &params,
+            &vk,
+            strategy,
+            &[&[]],
+            &mut transcript,
+        )
+    });
     let verify_time = start.elapsed();
 
     match result {
@@ -140,4 +229,124 @@ fn main() {
     println!("\nPerformance Summary:");
     println!("- Proof Generation Time: {:?}", proof_time);
     println!("- Proof Verification Time: {:?}", verify_time);
+
+    SizedRun {
+        size: rows,
+        times: PhaseTimes {
+            setup: setup_time,
+            prove: proof_time,
+            verify: verify_time,
+        },
+        proof_size: proof.len(),
+        prove_memory,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use labkit::reference::{self, SHA256_MESSAGE};
+    use labkit::tamper;
+    use halo2_proofs::dev::MockProver;
+    use halo2_proofs::plonk::{create_proof, keygen_pk, keygen_vk, verify_proof, SingleVerifier, VerifyingKey};
+    use halo2_proofs::poly::commitment::Params;
+    use halo2_proofs::transcript::{Blake2bRead, Blake2bWrite, Challenge255};
+    use std::cell::RefCell;
+
+    /// `Sha256Circuit` that records the digest words it computes.
+    struct DigestCircuit {
+        inner: Sha256Circuit,
+        digest: RefCell<Vec<u32>>,
+    }
+
+    impl Circuit<Fp> for DigestCircuit {
+        type Config = Table16Config;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                inner: self.inner.without_witnesses(),
+                digest: RefCell::default(),
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            Sha256Circuit::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let digest = self.inner.hash(config, &mut layouter)?;
+            let mut words = self.digest.borrow_mut();
+            words.clear();
+            for word in digest {
+                word.0.map(|v| words.push(v));
+            }
+            Ok(())
+        }
+    }
+
+    /// The initial hash value of SHA-256 (`halo2_gadgets` keeps its `IV` private).
+    const SHA256_IV: [u32; DIGEST_SIZE] = [
+        0x6a09_e667, 0xbb67_ae85, 0x3c6e_f372, 0xa54f_f53a, 0x510e_527f, 0x9b05_688c, 0x1f83_d9ab, 0x5be0_cd19,
+    ];
+
+    // `Table16Chip::digest` returns the digest as values rather than cells, so the
+    // circuit cannot expose it as a public input. Unlike the other backends, there is
+    // no public digest here for a wrong value to be rejected against.
+    #[test]
+    fn test_digest_matches_reference() {
+        let circuit = DigestCircuit {
+            inner: Sha256Circuit {
+                input: SHA256_MESSAGE.to_vec(),
+            },
+            digest: RefCell::default(),
+        };
+        let k = circuit_size::find_k(&circuit.inner, &[]).unwrap();
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // The message fits in one block, so adding the IV that the chip leaves out
+        // completes SHA-256
+        assert!(SHA256_MESSAGE.len() + 9 <= 64);
+        let digest: Vec<u8> = (circuit.digest.borrow().iter().zip(SHA256_IV))
+            .flat_map(|(word, iv)| word.wrapping_add(iv).to_be_bytes())
+            .collect();
+        assert_eq!(digest, reference::sha256(SHA256_MESSAGE));
+    }
+
+    /// Runs keygen for `message`'s length and proves it. Returns the parameters, the
+    /// verifying key and the proof.
+    fn setup_and_prove(message: &[u8]) -> (Params<EqAffine>, VerifyingKey<EqAffine>, Vec<u8>) {
+        let circuit = Sha256Circuit {
+            input: message.to_vec(),
+        };
+        let k = circuit_size::find_k(&circuit, &[]).unwrap();
+        let params = Params::<EqAffine>::new(k);
+        let vk = keygen_vk(&params, &circuit).unwrap();
+        let pk = keygen_pk(&params, vk.clone(), &circuit).unwrap();
+        let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+        create_proof(&params, &pk, &[circuit], &[&[]], ExampleRng::new(None), &mut transcript).unwrap();
+        (params, vk, transcript.finalize())
+    }
+
+    // The circuit has no public inputs, and every message of up to 55 bytes gives the
+    // same one-block circuit, so a proof for any of them verifies under the same key.
+    // The swapped-in proof therefore compresses two blocks, which needs its own key.
+    // The example refuses such messages, as the chained blocks are not SHA-256, but
+    // the proof is still a valid proof of another circuit.
+    #[test]
+    fn test_tampered_proofs_are_rejected() {
+        let (params, vk, proof) = setup_and_prove(SHA256_MESSAGE);
+        let (_, _, other) = setup_and_prove(&[0x61; 64]);
+
+        tamper::assert_rejects_tampering(&proof, &other, |bytes| {
+            let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(bytes);
+            let strategy = SingleVerifier::new(&params);
+            verify_proof(&params, &vk, strategy, &[&[]], &mut transcript).is_ok()
+        });
+    }
 }
@@ -0,0 +1,210 @@
+//! Repeated-run benchmarking.
+//!
+//! A single `Instant::now()` measurement varies a lot from run to run. `run` repeats a
+//! whole setup/prove/verify cycle, discards the warm-up runs and reports the mean,
+//! median, p95, standard deviation and range of every phase. Summaries can be saved as
+//! a named baseline and compared against later, e.g. before and after a library upgrade:
+//!
+//! ```text
+//! --bench [samples] [--warm-up n] [--save-baseline name] [--baseline name]
+//! ```
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::memory::MemoryStats;
+
+/// Samples taken when `--bench` is not followed by a count.
+pub const DEFAULT_SAMPLES: usize = 10;
+/// Warm-up runs when `--warm-up` is not given.
+pub const DEFAULT_WARM_UP: usize = 2;
+/// Median changes smaller than this (in percent) are reported as noise.
+pub const NOISE_THRESHOLD: f64 = 5.0;
+/// Directory holding saved baselines, one subdirectory per baseline name.
+pub const BASELINE_DIR: &str = "bench_baselines";
+
+/// Wall-clock time of each phase of one run.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PhaseTimes {
+    pub setup: Duration,
+    pub prove: Duration,
+    pub verify: Duration,
+}
+
+impl PhaseTimes {
+    pub const PHASES: [&'static str; 3] = ["setup", "prove", "verify"];
+
+    /// The phase times in the order of `PHASES`.
+    pub fn as_array(&self) -> [Duration; 3] {
+        [self.setup, self.prove, self.verify]
+    }
+}
+
+/// Costs of one run of an input-size or circuit-size sweep.
+#[derive(Clone, Copy, Debug)]
+pub struct SizedRun {
+    /// Circuit size: constraints, gates or rows depending on the proof system
+    pub size: usize,
+    pub times: PhaseTimes,
+    pub proof_size: usize,
+    pub prove_memory: MemoryStats,
+}
+
+/// Benchmark options parsed from the command line.
+#[derive(Clone, Debug)]
+pub struct BenchConfig {
+    pub samples: usize,
+    pub warm_up: usize,
+    /// Name under which to save the summaries
+    pub save_baseline: Option<String>,
+    /// Name of a saved baseline to compare against
+    pub baseline: Option<String>,
+}
+
+impl BenchConfig {
+    /// Parses `--bench [samples]` and its options. Returns `None` if `--bench` is absent.
+    pub fn from_args() -> Option<Self> {
+        let args: Vec<String> = std::env::args().collect();
+        let pos = args.iter().position(|a| a == "--bench")?;
+        let value_of = |flag: &str| {
+            let i = args.iter().position(|a| a == flag)?;
+            args.get(i + 1).cloned()
+        };
+        let count = |value: Option<String>, default: usize| {
+            value
+                .filter(|v| !v.starts_with("--"))
+                .map_or(default, |v| v.parse().expect("sample counts must be positive integers"))
+        };
+
+        let config = BenchConfig {
+            samples: count(args.get(pos + 1).cloned(), DEFAULT_SAMPLES),
+            warm_up: count(value_of("--warm-up"), DEFAULT_WARM_UP),
+            save_baseline: value_of("--save-baseline"),
+            baseline: value_of("--baseline"),
+        };
+        assert!(config.samples > 0, "at least one sample is required");
+        Some(config)
+    }
+}
+
+/// Statistics over the samples of one phase, in milliseconds.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct Summary {
+    pub samples: usize,
+    pub mean: f64,
+    pub median: f64,
+    pub p95: f64,
+    pub std_dev: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl Summary {
+    pub fn from_samples(samples: &[Duration]) -> Self {
+        let mut ms: Vec<f64> = samples.iter().map(|d| d.as_secs_f64() * 1000.0).collect();
+        ms.sort_by(|a, b| a.total_cmp(b));
+        let n = ms.len();
+        let mean = ms.iter().sum::<f64>() / n as f64;
+        let median = if n.is_multiple_of(2) {
+            (ms[n / 2 - 1] + ms[n / 2]) / 2.0
+        } else {
+            ms[n / 2]
+        };
+        // Nearest-rank percentile
+        let p95 = ms[((0.95 * n as f64).ceil() as usize).clamp(1, n) - 1];
+        // Sample standard deviation (0 for a single sample)
+        let std_dev = if n > 1 {
+            (ms.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1) as f64).sqrt()
+        } else {
+            0.0
+        };
+
+        Summary {
+            samples: n,
+            mean,
+            median,
+            p95,
+            std_dev,
+            min: ms[0],
+            max: ms[n - 1],
+        }
+    }
+}
+
+fn baseline_path(baseline: &str, name: &str) -> PathBuf {
+    PathBuf::from(BASELINE_DIR).join(baseline).join(format!("{}.json", name))
+}
+
+/// Runs `f` `config.warm_up` times without recording, then `config.samples` times,
+/// prints a summary of every phase and saves or compares baselines as configured.
+pub fn run(
+    name: &str,
+    config: &BenchConfig,
+    mut f: impl FnMut() -> PhaseTimes,
+) -> BTreeMap<String, Summary> {
+    for i in 0..config.warm_up {
+        println!("\n== {} warm-up {}/{} ==", name, i + 1, config.warm_up);
+        f();
+    }
+    let mut runs = Vec::with_capacity(config.samples);
+    for i in 0..config.samples {
+        println!("\n== {} sample {}/{} ==", name, i + 1, config.samples);
+        runs.push(f());
+    }
+
+    let summaries: BTreeMap<String, Summary> = PhaseTimes::PHASES
+        .iter()
+        .enumerate()
+        .map(|(i, phase)| {
+            let samples: Vec<Duration> = runs.iter().map(|t| t.as_array()[i]).collect();
+            (phase.to_string(), Summary::from_samples(&samples))
+        })
+        .collect();
+
+    let baseline: Option<BTreeMap<String, Summary>> = config.baseline.as_ref().map(|b| {
+        let path = baseline_path(b, name);
+        let json = fs::read_to_string(&path)
+            .unwrap_or_else(|e| panic!("cannot read baseline {}: {}", path.display(), e));
+        serde_json::from_str(&json).expect("malformed baseline file")
+    });
+
+    println!(
+        "\n{} ({} samples, {} warm-up), times in ms:",
+        name, config.samples, config.warm_up
+    );
+    println!(
+        "{:<8} {:>11} {:>11} {:>11} {:>11} {:>11} {:>11}",
+        "phase", "mean", "median", "p95", "std dev", "min", "max"
+    );
+    for phase in PhaseTimes::PHASES {
+        let s = &summaries[phase];
+        print!(
+            "{:<8} {:>11.3} {:>11.3} {:>11.3} {:>11.3} {:>11.3} {:>11.3}",
+            phase, s.mean, s.median, s.p95, s.std_dev, s.min, s.max
+        );
+        if let Some(old) = baseline.as_ref().and_then(|b| b.get(phase)) {
+            let change = (s.median - old.median) / old.median * 100.0;
+            let verdict = if change.abs() < NOISE_THRESHOLD {
+                "within noise"
+            } else if change > 0.0 {
+                "REGRESSED"
+            } else {
+                "improved"
+            };
+            print!("  median {:+.1}% vs baseline ({})", change, verdict);
+        }
+        println!();
+    }
+
+    if let Some(b) = &config.save_baseline {
+        let path = baseline_path(b, name);
+        fs::create_dir_all(path.parent().unwrap()).expect("cannot create baseline directory");
+        let json = serde_json::to_string_pretty(&summaries).expect("summaries are serializable");
+        fs::write(&path, json).expect("cannot write baseline");
+        println!("Baseline saved to {}", path.display());
+    }
+    summaries
+}
@@ -0,0 +1,66 @@
+//! Binding proofs to an application context.
+//!
+//! A transcript that only starts from a fixed label such as `b"range_proof"` accepts the
+//! same proof in every application using that label, so a proof can be replayed
+//! elsewhere. A `ProofContext` starts the transcript from a domain separator and then
+//! absorbs arbitrary context bytes (a chain ID, a transaction hash, ...) before the
+//! proof system adds anything. Prover and verifier must use the same domain and context.
+//!
+//! The examples read the context bytes from `--context <string>`. Without the flag, the
+//! context is empty.
+use merlin::Transcript;
+
+/// A domain separator plus the context bytes a proof is bound to.
+#[derive(Clone, Debug)]
+pub struct ProofContext {
+    pub domain: &'static [u8],
+    pub data: Vec<u8>,
+}
+
+impl ProofContext {
+    pub fn new(domain: &'static [u8], data: impl Into<Vec<u8>>) -> Self {
+        ProofContext {
+            domain,
+            data: data.into(),
+        }
+    }
+
+    /// A context for `domain` with the bytes of `--context <string>`, or empty bytes
+    /// if the flag is absent.
+    pub fn from_args(domain: &'static [u8]) -> Self {
+        let args: Vec<String> = std::env::args().collect();
+        let data = args
+            .iter()
+            .position(|a| a == "--context")
+            .map(|pos| args.get(pos + 1).expect("--context needs a value").clone())
+            .unwrap_or_default();
+        Self::new(domain, data)
+    }
+
+    /// A fresh transcript bound to this context.
+    pub fn transcript(&self) -> Transcript {
+        let mut transcript = Transcript::new(self.domain);
+        transcript.append_message(b"context", &self.data);
+        transcript
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn challenge(context: &ProofContext) -> [u8; 32] {
+        let mut bytes = [0; 32];
+        context.transcript().challenge_bytes(b"challenge", &mut bytes);
+        bytes
+    }
+
+    #[test]
+    fn test_challenges_depend_on_domain_and_context() {
+        let context = ProofContext::new(b"range_proof", "chain-1");
+        assert_eq!(challenge(&context), challenge(&context.clone()));
+        assert_ne!(challenge(&context), challenge(&ProofContext::new(b"range_proof", "chain-2")));
+        assert_ne!(challenge(&context), challenge(&ProofContext::new(b"range_proof", "")));
+        assert_ne!(challenge(&context), challenge(&ProofContext::new(b"other_app", "chain-1")));
+    }
+}
@@ -0,0 +1,31 @@
+//! Seed corpus for the fuzz targets in `fuzz/`.
+//!
+//! With `--save-corpus <dir>` the examples write the serialized proofs (and keys) they
+//! produce to `<dir>/<target>/<name>`, where `<target>` is the fuzz target that decodes
+//! that kind of bytes and `<name>` identifies the example. Running an example with
+//! `--save-corpus fuzz/corpus` therefore seeds cargo-fuzz with valid encodings, so the
+//! fuzzer starts from inputs that get past the deserializer and into the verifier.
+//! Without the flag nothing is written.
+use std::fs;
+use std::path::PathBuf;
+
+/// The directory given with `--save-corpus <dir>`, if any.
+pub fn dir_from_args() -> Option<PathBuf> {
+    let args: Vec<String> = std::env::args().collect();
+    args.iter()
+        .position(|a| a == "--save-corpus")
+        .map(|pos| PathBuf::from(args.get(pos + 1).expect("--save-corpus needs a directory")))
+}
+
+/// Writes `bytes` as the seed `name` of fuzz target `target` when `--save-corpus` is
+/// given.
+pub fn save_from_args(target: &str, name: &str, bytes: &[u8]) {
+    let Some(dir) = dir_from_args() else {
+        return;
+    };
+    let dir = dir.join(target);
+    fs::create_dir_all(&dir).expect("cannot create corpus directory");
+    let path = dir.join(name);
+    fs::write(&path, bytes).expect("cannot write corpus file");
+    println!("Saved {} bytes to {}", bytes.len(), path.display());
+}
@@ -0,0 +1,30 @@
+//! Zero-knowledge sanity check.
+//!
+//! A zero-knowledge prover blinds every proof with fresh randomness, so proving the same
+//! statement with the same witness twice gives two different proofs. A prover that
+//! returns the same bytes both times uses no randomness: its proof is a function of the
+//! witness and cannot hide it. `check` proves twice and tells the two cases apart. The
+//! tests assert `Hiding::Randomized` for every configuration meant to be zero-knowledge
+//! and `Hiding::Deterministic` for the ones known not to hide, so those stay flagged
+//! and a change on either side fails a test.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Hiding {
+    /// Two proofs of the same statement differ.
+    Randomized,
+    /// Two proofs of the same statement are identical, so the proof does not hide the
+    /// witness.
+    Deterministic,
+}
+
+/// Calls `prove` twice and compares the serialized proofs. Both calls must prove the
+/// same statement with the same witness; each draws its own randomness, if any.
+pub fn check(mut prove: impl FnMut() -> Vec<u8>) -> Hiding {
+    let first = prove();
+    let second = prove();
+    if first == second {
+        Hiding::Deterministic
+    } else {
+        Hiding::Randomized
+    }
+}
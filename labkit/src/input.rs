@@ -0,0 +1,201 @@
+//! Circuit inputs from JSON files in the schema of the snarkjs examples.
+//!
+//! With `--input <file>` an example reads its inputs from `file` instead of using its
+//! built-in ones. The files have the shape of the `input.json` next to the matching
+//! circom circuit in `snarkjs/`, so one file drives every library:
+//! - cubic expression: `{"x": "3"}`
+//! - range proof: `{"in": [value, bound]}`, where `bound = 2^bits` and the statement is
+//!   `value < bound`
+//! - SHA-256: `{"in": [0, 1, 1, 0, ...]}`, the message as bits, most significant bit of
+//!   each byte first
+//!
+//! Numbers may be JSON numbers or decimal strings, as in snarkjs. The inputs of
+//! `crate::reference` are kept in this form in `inputs/`.
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer};
+
+/// The file given with `--input <file>`, if any.
+pub fn path_from_args() -> Option<PathBuf> {
+    let args: Vec<String> = std::env::args().collect();
+    args.iter()
+        .position(|a| a == "--input")
+        .map(|pos| PathBuf::from(args.get(pos + 1).expect("--input needs a file")))
+}
+
+/// Reads the inputs from the file given with `--input`, or `None` without the flag.
+pub fn from_args<T: DeserializeOwned>() -> Option<T> {
+    path_from_args().map(|path| load(&path).unwrap_or_else(|e| panic!("cannot load {}: {}", path.display(), e)))
+}
+
+/// Reads inputs of type `T` from the JSON file at `path`.
+pub fn load<T: DeserializeOwned>(path: &Path) -> Result<T, InputError> {
+    let json = fs::read_to_string(path).map_err(InputError::Read)?;
+    serde_json::from_str(&json).map_err(InputError::Parse)
+}
+
+#[derive(Debug)]
+pub enum InputError {
+    /// The file could not be read.
+    Read(std::io::Error),
+    /// The file is not valid JSON or does not have the expected schema.
+    Parse(serde_json::Error),
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputError::Read(e) => write!(f, "{}", e),
+            InputError::Parse(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for InputError {}
+
+/// Input of the cubic expression `x^3 + x + 5 = y`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub struct CubicInput {
+    #[serde(deserialize_with = "decimal")]
+    pub x: u64,
+}
+
+/// Input of the range proof: `value` is claimed to lie in `[0, 2^bits)`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "RangeJson")]
+pub struct RangeInput {
+    pub value: u64,
+    pub bits: usize,
+}
+
+/// Input of the SHA-256 examples: the message to hash.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "Sha256Json")]
+pub struct Sha256Input {
+    pub message: Vec<u8>,
+}
+
+/// A number as snarkjs accepts it.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Decimal {
+    Number(u64),
+    String(String),
+}
+
+impl TryFrom<Decimal> for u128 {
+    type Error = String;
+
+    fn try_from(decimal: Decimal) -> Result<Self, String> {
+        match decimal {
+            Decimal::Number(n) => Ok(n.into()),
+            Decimal::String(s) => s.parse().map_err(|_| format!("{:?} is not an unsigned integer", s)),
+        }
+    }
+}
+
+fn decimal<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    let n = u128::try_from(Decimal::deserialize(deserializer)?).map_err(serde::de::Error::custom)?;
+    u64::try_from(n).map_err(|_| serde::de::Error::custom(format!("{} does not fit in 64 bits", n)))
+}
+
+#[derive(Deserialize)]
+struct RangeJson {
+    #[serde(rename = "in")]
+    values: Vec<Decimal>,
+}
+
+impl TryFrom<RangeJson> for RangeInput {
+    type Error = String;
+
+    fn try_from(json: RangeJson) -> Result<Self, String> {
+        let [value, bound]: [Decimal; 2] =
+            json.values.try_into().map_err(|_| "\"in\" must be [value, bound]".to_string())?;
+        let (value, bound) = (u128::try_from(value)?, u128::try_from(bound)?);
+        let value = u64::try_from(value).map_err(|_| format!("{} does not fit in 64 bits", value))?;
+        // The Rust circuits take the range as a bit width
+        if !bound.is_power_of_two() || bound == 1 || bound > 1 << 64 {
+            return Err(format!("the bound must be 2^bits with 1 <= bits <= 64, got {}", bound));
+        }
+        Ok(RangeInput {
+            value,
+            bits: bound.trailing_zeros() as usize,
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct Sha256Json {
+    #[serde(rename = "in")]
+    bits: Vec<Decimal>,
+}
+
+impl TryFrom<Sha256Json> for Sha256Input {
+    type Error = String;
+
+    fn try_from(json: Sha256Json) -> Result<Self, String> {
+        if !json.bits.len().is_multiple_of(8) {
+            return Err(format!("the message must be whole bytes, got {} bits", json.bits.len()));
+        }
+        let bits = json
+            .bits
+            .into_iter()
+            .map(|bit| match u128::try_from(bit)? {
+                bit @ (0 | 1) => Ok(bit as u8),
+                bit => Err(format!("bits must be 0 or 1, got {}", bit)),
+            })
+            .collect::<Result<Vec<u8>, String>>()?;
+        let message = bits.chunks(8).map(|byte| byte.iter().fold(0, |acc, bit| acc << 1 | bit)).collect();
+        Ok(Sha256Input { message })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reference::{CUBIC_X, RANGE_BITS, RANGE_VALUE, SHA256_MESSAGE};
+
+    fn vector<T: DeserializeOwned>(name: &str) -> T {
+        load(&Path::new(env!("CARGO_MANIFEST_DIR")).join("inputs").join(name)).unwrap()
+    }
+
+    #[test]
+    fn test_vectors_match_reference() {
+        assert_eq!(vector::<CubicInput>("cubic.json").x, CUBIC_X);
+        let range: RangeInput = vector("range_proof.json");
+        assert_eq!((range.value, range.bits), (RANGE_VALUE, RANGE_BITS));
+        assert_eq!(vector::<Sha256Input>("sha256.json").message, SHA256_MESSAGE);
+    }
+
+    #[test]
+    fn test_snarkjs_inputs_are_read() {
+        // The inputs of the snarkjs examples, with numbers as strings and as JSON numbers
+        let cubic: CubicInput = serde_json::from_str(r#"{"x": "3"}"#).unwrap();
+        assert_eq!(cubic, serde_json::from_str(r#"{"x": 3}"#).unwrap());
+        let range: RangeInput = serde_json::from_str(r#"{"in": [10086, 4294967296]}"#).unwrap();
+        assert_eq!(range, RangeInput { value: 10086, bits: 32 });
+        let bits = r#"{"in": [0, 1, 1, 0, 1, 0, 0, 0, 0, 1, 1, 0, 1, 0, 0, 1]}"#;
+        assert_eq!(serde_json::from_str::<Sha256Input>(bits).unwrap().message, b"hi");
+    }
+
+    #[test]
+    fn test_malformed_inputs_are_refused() {
+        for json in [
+            r#"{"x": "-3"}"#,
+            r#"{"x": "18446744073709551616"}"#,
+            r#"{"in": [10086]}"#,
+            r#"{"in": [10086, 1000]}"#,
+            r#"{"in": [10086, 1]}"#,
+            r#"{"in": [0, 1, 1, 0, 1, 0, 0]}"#,
+            r#"{"in": [0, 1, 1, 0, 1, 0, 0, 2]}"#,
+        ] {
+            let cubic = serde_json::from_str::<CubicInput>(json);
+            let range = serde_json::from_str::<RangeInput>(json);
+            let sha256 = serde_json::from_str::<Sha256Input>(json);
+            assert!(cubic.is_err() && range.is_err() && sha256.is_err(), "{} should be refused", json);
+        }
+    }
+}
@@ -0,0 +1,110 @@
+//! Circuit-size ladder.
+//!
+//! The `cubic_ladder` examples chain the cubic_expression statement N times,
+//! `x_{i+1} = x_i^3 + x_i + 5`, and prove knowledge of `x_0` for a public `x_N`.
+//! In R1CS every step costs exactly two multiplication constraints (`x_i^2` and
+//! `x_i^2 * x_i`), so `2^(log_size - 1)` steps give `2^log_size` constraints. Sweeping
+//! `log_size` from 4 to 22 shows how each prover scales with circuit size; PLONKish
+//! backends report their own row or gate count for the same number of steps.
+//!
+//! Select the sizes with `--log-sizes 4..22` (inclusive range) or `--log-sizes 4,8,12`.
+use std::ops::RangeInclusive;
+
+use crate::bench::SizedRun;
+
+/// Sizes swept when `--log-sizes` is not given. Larger sizes take minutes and several
+/// GiB of memory on most backends, so they have to be asked for explicitly.
+pub const DEFAULT_LOG_SIZES: RangeInclusive<u32> = 4..=16;
+/// Largest supported `log_size`.
+pub const MAX_LOG_SIZE: u32 = 22;
+/// R1CS constraints per ladder step.
+pub const CONSTRAINTS_PER_STEP: usize = 2;
+/// The constant added in every step.
+pub const STEP_CONSTANT: u64 = 5;
+
+/// Number of ladder steps with `2^log_size` R1CS constraints.
+pub fn steps(log_size: u32) -> usize {
+    (1usize << log_size) / CONSTRAINTS_PER_STEP
+}
+
+/// Parses `--log-sizes` from the command line, falling back to `DEFAULT_LOG_SIZES`.
+pub fn log_sizes_from_args() -> Vec<u32> {
+    let args: Vec<String> = std::env::args().collect();
+    match args.iter().position(|a| a == "--log-sizes").and_then(|pos| args.get(pos + 1)) {
+        Some(spec) => parse_log_sizes(spec),
+        None => DEFAULT_LOG_SIZES.collect(),
+    }
+}
+
+/// Parses a list of log sizes: `a..b` or `a..=b` (both inclusive) or a comma-separated
+/// list.
+pub fn parse_log_sizes(spec: &str) -> Vec<u32> {
+    let parse = |s: &str| -> u32 {
+        let log_size = s.trim().parse().expect("log sizes must be integers");
+        assert!(
+            (1..=MAX_LOG_SIZE).contains(&log_size),
+            "log sizes must be between 1 and {}",
+            MAX_LOG_SIZE
+        );
+        log_size
+    };
+    match spec.split_once("..") {
+        Some((from, to)) => (parse(from)..=parse(to.trim_start_matches('='))).collect(),
+        None => spec.split(',').map(parse).collect(),
+    }
+}
+
+/// Runs `run` with the number of steps for every size and prints how the circuit
+/// size, proving time, proof size and prover memory grow. `size_label` names the
+/// unit of `SizedRun::size` (e.g. "constraints" or "rows").
+pub fn sweep(
+    name: &str,
+    size_label: &str,
+    log_sizes: &[u32],
+    mut run: impl FnMut(usize) -> SizedRun,
+) -> Vec<(usize, SizedRun)> {
+    let mut results = Vec::new();
+    for &log_size in log_sizes {
+        let steps = steps(log_size);
+        println!("\n== {} with {} steps (2^{} R1CS constraints) ==", name, steps, log_size);
+        results.push((steps, run(steps)));
+    }
+
+    println!("\n{} circuit-size scaling:", name);
+    println!(
+        "{:>9} {:>12} {:>14} {:>14} {:>14} {:>12} {:>15}",
+        "steps", size_label, "setup time", "prove time", "verify time", "proof size", "prove heap"
+    );
+    for (steps, r) in &results {
+        println!(
+            "{:>9} {:>12} {:>11.3} ms {:>11.3} ms {:>11.3} ms {:>10} B {:>11.2} MiB",
+            steps,
+            r.size,
+            r.times.setup.as_secs_f64() * 1000.0,
+            r.times.prove.as_secs_f64() * 1000.0,
+            r.times.verify.as_secs_f64() * 1000.0,
+            r.proof_size,
+            r.prove_memory.peak_heap as f64 / (1024.0 * 1024.0),
+        );
+    }
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_sizes_parse_ranges_and_lists() {
+        assert_eq!(parse_log_sizes("4..6"), [4, 5, 6]);
+        assert_eq!(parse_log_sizes("4..=6"), [4, 5, 6]);
+        assert_eq!(parse_log_sizes("4,8, 12"), [4, 8, 12]);
+        assert_eq!(parse_log_sizes("10"), [10]);
+    }
+
+    #[test]
+    #[should_panic(expected = "log sizes must be between 1 and 22")]
+    fn test_log_sizes_above_the_maximum_are_refused() {
+        parse_log_sizes("4..23");
+    }
+}
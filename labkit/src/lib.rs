@@ -0,0 +1,11 @@
+pub mod bench;
+pub mod context;
+pub mod corpus;
+pub mod hiding;
+pub mod input;
+pub mod ladder;
+pub mod memory;
+pub mod reference;
+pub mod rng;
+pub mod scaling;
+pub mod tamper;
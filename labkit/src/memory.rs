@@ -0,0 +1,135 @@
+//! Peak memory tracking.
+//!
+//! `PeakAllocator` records the current heap size, its high-water mark and the number of
+//! allocations. `measure` resets these (and the kernel's peak RSS counter) before a
+//! phase, so every phase of a benchmark (setup, prove, verify) reports its own peak heap
+//! and peak RSS. A library cannot pick the allocator of the programs linking it, so each
+//! example binary installs it itself:
+//!
+//! ```ignore
+//! #[global_allocator]
+//! static ALLOCATOR: labkit::memory::PeakAllocator = labkit::memory::PeakAllocator;
+//! ```
+//!
+//! Without it the heap figures stay at zero; peak RSS is measured either way.
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Heap size, high-water mark and allocation count, updated by `PeakAllocator`.
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+/// The system allocator, counting the bytes it hands out.
+pub struct PeakAllocator;
+
+impl PeakAllocator {
+    fn grow(&self, size: usize) {
+        let current = CURRENT.fetch_add(size, Ordering::Relaxed) + size;
+        PEAK.fetch_max(current, Ordering::Relaxed);
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn shrink(&self, size: usize) {
+        CURRENT.fetch_sub(size, Ordering::Relaxed);
+    }
+}
+
+unsafe impl GlobalAlloc for PeakAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            self.grow(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            self.grow(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        self.shrink(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            self.shrink(layout.size());
+            self.grow(new_size);
+        }
+        new_ptr
+    }
+}
+
+/// Memory usage of one phase.
+#[derive(Clone, Copy, Debug)]
+pub struct MemoryStats {
+    /// Heap high-water mark, in bytes
+    pub peak_heap: usize,
+    /// Heap size when the phase started, in bytes
+    pub start_heap: usize,
+    /// Number of allocations (including reallocations) made during the phase
+    pub allocations: usize,
+    /// Peak resident set size, in bytes (`None` where `/proc` is not available)
+    pub peak_rss: Option<usize>,
+}
+
+impl fmt::Display for MemoryStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "peak heap {:.2} MiB (+{:.2} MiB), {} allocations, peak RSS ",
+            mib(self.peak_heap),
+            mib(self.peak_heap.saturating_sub(self.start_heap)),
+            self.allocations
+        )?;
+        match self.peak_rss {
+            Some(rss) => write!(f, "{:.2} MiB", mib(rss)),
+            None => write!(f, "n/a"),
+        }
+    }
+}
+
+fn mib(bytes: usize) -> f64 {
+    bytes as f64 / (1024.0 * 1024.0)
+}
+
+/// Resets the heap high-water mark, the allocation count and the peak RSS.
+fn reset_peak() -> usize {
+    let current = CURRENT.load(Ordering::Relaxed);
+    PEAK.store(current, Ordering::Relaxed);
+    ALLOCATIONS.store(0, Ordering::Relaxed);
+    // Writing 5 to clear_refs resets VmHWM to the current RSS (Linux only)
+    let _ = std::fs::write("/proc/self/clear_refs", "5");
+    current
+}
+
+/// Reads VmHWM (peak RSS) from `/proc/self/status`.
+fn peak_rss() -> Option<usize> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+    let kib: usize = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kib * 1024)
+}
+
+/// Runs `f` as the phase `phase`, prints its memory usage and returns its result
+/// together with the measured statistics.
+pub fn measure<T>(phase: &str, f: impl FnOnce() -> T) -> (T, MemoryStats) {
+    let start_heap = reset_peak();
+    let result = f();
+    let stats = MemoryStats {
+        peak_heap: PEAK.load(Ordering::Relaxed),
+        start_heap,
+        allocations: ALLOCATIONS.load(Ordering::Relaxed),
+        peak_rss: peak_rss(),
+    };
+    println!("{} memory: {}", phase, stats);
+    (result, stats)
+}
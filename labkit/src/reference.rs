@@ -0,0 +1,49 @@
+//! Inputs and native references shared by the differential tests.
+//!
+//! Every Rust backend proves the same three statements: the cubic expression, a 32-bit
+//! range proof and SHA-256. The tests next to each example feed it the inputs below,
+//! compare the circuit's public outputs with the plain Rust computation here, and
+//! check that it rejects the same invalid witnesses.
+use sha2::{Digest, Sha256};
+
+/// Secret input of the cubic expression.
+pub const CUBIC_X: u64 = 3;
+/// Value proven to be in range.
+pub const RANGE_VALUE: u64 = 1_234_567_890;
+/// Width of the range, i.e. values in `[0, 2^RANGE_BITS)` are accepted.
+pub const RANGE_BITS: usize = 32;
+/// Message hashed by the SHA-256 examples.
+pub const SHA256_MESSAGE: &[u8] = b"Hello, World!";
+
+/// Values every range proof must reject: the first value past the range and one far
+/// outside it. Both fit in every field the backends use.
+pub const OUT_OF_RANGE_VALUES: [u64; 2] = [1 << RANGE_BITS, (1 << 40) + RANGE_VALUE];
+
+/// `x^3 + x + 5`, the public output of the cubic expression. The constant is the one
+/// of the snarkjs circuits (`snarkjs/cubic_*/cubic_expression.circom`), so one reference
+/// and one `input.json` describe the same statement in every library.
+pub fn cubic(x: u64) -> u64 {
+    x * x * x + x + 5
+}
+
+/// Public outputs every cubic circuit must reject for `CUBIC_X`: off by one, and the
+/// output for a different input.
+pub fn wrong_cubic_outputs() -> [u64; 2] {
+    [cubic(CUBIC_X) + 1, cubic(CUBIC_X + 1)]
+}
+
+/// SHA-256 of `message`, computed by the `sha2` crate.
+pub fn sha256(message: &[u8]) -> [u8; 32] {
+    Sha256::digest(message).into()
+}
+
+/// Digests every SHA-256 circuit must reject for `SHA256_MESSAGE`: the correct digest
+/// with its first or last bit flipped, and the digest of a message one byte shorter.
+pub fn wrong_digests() -> Vec<[u8; 32]> {
+    let digest = sha256(SHA256_MESSAGE);
+    let mut first = digest;
+    first[0] ^= 0x80;
+    let mut last = digest;
+    last[31] ^= 0x01;
+    vec![first, last, sha256(&SHA256_MESSAGE[..SHA256_MESSAGE.len() - 1])]
+}
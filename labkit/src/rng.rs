@@ -0,0 +1,98 @@
+//! Reproducible randomness.
+//!
+//! By default the examples draw their randomness from the operating system. With
+//! `--seed <n>`, every random value an example draws itself (setup, witness sampling and
+//! proving) comes from a ChaCha20 stream seeded with `n` instead, so a run can be
+//! repeated exactly, e.g. to debug a failure or to produce golden files. Runs with the
+//! same seed produce the same proofs, which is exactly what a zero-knowledge prover must
+//! not do in production (see `crate::hiding`), so the flag is for debugging and tests.
+//!
+//! A seed only fixes what is drawn from this RNG. Examples whose prover samples further
+//! randomness itself, or none at all, call `refuse_seed` instead of offering the flag.
+use rand_chacha::ChaCha20Rng;
+use rand_core::{CryptoRng, OsRng, RngCore, SeedableRng};
+
+/// The seed given with `--seed <n>`, if any.
+pub fn seed_from_args() -> Option<u64> {
+    let args: Vec<String> = std::env::args().collect();
+    args.iter().position(|a| a == "--seed").map(|pos| {
+        args.get(pos + 1)
+            .expect("--seed needs a value")
+            .parse()
+            .expect("--seed must be an unsigned 64-bit integer")
+    })
+}
+
+/// Exits with an error if `--seed` was given, for examples where a seed would not
+/// change what a run repeats. `why` explains this, e.g. that the prover samples
+/// randomness of its own, so a seed would fix the witness but not the proof.
+pub fn refuse_seed(why: &str) {
+    if std::env::args().any(|a| a == "--seed") {
+        eprintln!("error: --seed is not supported: {}", why);
+        std::process::exit(1);
+    }
+}
+
+/// The randomness of one run: a ChaCha20 stream for a seed, or the operating system's.
+pub struct ExampleRng {
+    seeded: Option<ChaCha20Rng>,
+    os: OsRng,
+}
+
+impl ExampleRng {
+    pub fn new(seed: Option<u64>) -> Self {
+        ExampleRng {
+            seeded: seed.map(ChaCha20Rng::seed_from_u64),
+            os: OsRng,
+        }
+    }
+
+    /// Seeded with `--seed <n>`, or OS randomness if the flag is absent.
+    pub fn from_args() -> Self {
+        Self::new(seed_from_args())
+    }
+
+    fn inner(&mut self) -> &mut dyn RngCore {
+        match &mut self.seeded {
+            Some(rng) => rng,
+            None => &mut self.os,
+        }
+    }
+}
+
+impl RngCore for ExampleRng {
+    fn next_u32(&mut self) -> u32 {
+        self.inner().next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.inner().next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.inner().fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.inner().try_fill_bytes(dest)
+    }
+}
+
+// ChaCha20 and the OS generator are both cryptographically secure
+impl CryptoRng for ExampleRng {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seed_reproduces_the_stream() {
+        let stream = |seed| {
+            let mut rng = ExampleRng::new(seed);
+            (0..4).map(|_| rng.next_u64()).collect::<Vec<_>>()
+        };
+        assert_eq!(stream(Some(7)), stream(Some(7)));
+        assert_ne!(stream(Some(7)), stream(Some(8)));
+        assert_ne!(stream(None), stream(None));
+    }
+}
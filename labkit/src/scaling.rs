@@ -0,0 +1,148 @@
+//! Scaling sweeps.
+//!
+//! The provers parallelise with rayon. `sweep` runs the same workload inside rayon
+//! pools of different sizes, so the speedup of each prover can be compared on the
+//! same machine. Select it with `--threads` (default counts) or `--threads 1,2,4`.
+//!
+//! `input_size_sweep` instead proves the same circuit for growing inputs (e.g. SHA-256
+//! messages of 32 B to 4 KB), selected with `--input-len` or `--input-len 64,1KB`.
+use std::time::Duration;
+
+use crate::bench::SizedRun;
+
+/// Message lengths, in bytes, used when `--input-len` is not followed by a list.
+pub const DEFAULT_INPUT_LENGTHS: [usize; 5] = [32, 64, 256, 1024, 4096];
+
+/// Powers of two below the number of available cores, followed by that number.
+pub fn default_thread_counts() -> Vec<usize> {
+    let n = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let mut counts: Vec<usize> = std::iter::successors(Some(1), |t| Some(t * 2))
+        .take_while(|&t| t < n)
+        .collect();
+    counts.push(n);
+    counts
+}
+
+/// Parses `--threads [1,2,4,...]` from the command line.
+///
+/// Returns `None` if the flag is absent and the default counts if no list follows it.
+pub fn thread_counts_from_args() -> Option<Vec<usize>> {
+    let args: Vec<String> = std::env::args().collect();
+    let pos = args.iter().position(|a| a == "--threads")?;
+    match args.get(pos + 1).filter(|a| !a.starts_with("--")) {
+        Some(list) => Some(
+            list.split(',')
+                .map(|t| t.trim().parse().expect("thread counts must be positive integers"))
+                .collect(),
+        ),
+        None => Some(default_thread_counts()),
+    }
+}
+
+/// Runs `run` once per entry of `counts` inside a rayon pool with that many threads,
+/// then prints the time `run` reports (e.g. the prove time) and the speedup relative
+/// to the first entry.
+pub fn sweep(
+    name: &str,
+    counts: &[usize],
+    mut run: impl FnMut() -> Duration + Send,
+) -> Vec<(usize, Duration)> {
+    let mut results = Vec::new();
+    for &threads in counts {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .expect("failed to build thread pool");
+        println!("\n== {} with {} thread(s) ==", name, threads);
+        results.push((threads, pool.install(&mut run)));
+    }
+
+    let (base_threads, base_time) = results[0];
+    println!("\n{} thread scaling:", name);
+    println!("{:>8} {:>14} {:>9} {:>11}", "threads", "time", "speedup", "efficiency");
+    for (threads, time) in &results {
+        let speedup = base_time.as_secs_f64() / time.as_secs_f64();
+        let efficiency = speedup * base_threads as f64 / *threads as f64;
+        println!(
+            "{:>8} {:>11.3} ms {:>8.2}x {:>10.1}%",
+            threads,
+            time.as_secs_f64() * 1000.0,
+            speedup,
+            efficiency * 100.0
+        );
+    }
+    results
+}
+
+/// Parses a length such as `64`, `64B`, `1KB` or `4K` into bytes.
+pub fn parse_length(s: &str) -> Option<usize> {
+    let s = s.trim().to_ascii_uppercase();
+    let (digits, unit) = match s.strip_suffix("KB").or_else(|| s.strip_suffix('K')) {
+        Some(digits) => (digits, 1024),
+        None => (s.strip_suffix('B').unwrap_or(&s), 1),
+    };
+    digits.parse::<usize>().ok().map(|n| n * unit)
+}
+
+/// Parses `--input-len [32,64,1KB,...]` from the command line.
+///
+/// Returns `None` if the flag is absent and `DEFAULT_INPUT_LENGTHS` if no list follows it.
+pub fn input_lengths_from_args() -> Option<Vec<usize>> {
+    let args: Vec<String> = std::env::args().collect();
+    let pos = args.iter().position(|a| a == "--input-len")?;
+    match args.get(pos + 1).filter(|a| !a.starts_with("--")) {
+        Some(list) => Some(
+            list.split(',')
+                .map(|l| parse_length(l).expect("input lengths must look like 64, 64B, 1KB or 4K"))
+                .collect(),
+        ),
+        None => Some(DEFAULT_INPUT_LENGTHS.to_vec()),
+    }
+}
+
+/// Number of 512-bit SHA-256 blocks for a `len`-byte message, including padding.
+pub fn sha256_blocks(len: usize) -> usize {
+    (len * 8 + 1 + 64).div_ceil(512)
+}
+
+/// A deterministic `len`-byte message.
+pub fn sample_message(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i % 251) as u8).collect()
+}
+
+/// Runs `run` once per message length and prints circuit size, prove time, proof
+/// size and prover memory against the number of SHA-256 blocks. `size_label` names
+/// the unit of `SizedRun::size` (e.g. "constraints" or "rows").
+pub fn input_size_sweep(
+    name: &str,
+    size_label: &str,
+    lengths: &[usize],
+    mut run: impl FnMut(usize) -> SizedRun,
+) -> Vec<(usize, SizedRun)> {
+    let mut results = Vec::new();
+    for &len in lengths {
+        println!("\n== {} with a {}-byte input ({} blocks) ==", name, len, sha256_blocks(len));
+        results.push((len, run(len)));
+    }
+
+    println!("\n{} input-size scaling:", name);
+    println!(
+        "{:>8} {:>7} {:>12} {:>14} {:>12} {:>15} {:>15}",
+        "bytes", "blocks", size_label, "prove time", "proof size", "prove heap", "peak RSS"
+    );
+    for (len, r) in &results {
+        println!(
+            "{:>8} {:>7} {:>12} {:>11.3} ms {:>10} B {:>11.2} MiB {:>15}",
+            len,
+            sha256_blocks(*len),
+            r.size,
+            r.times.prove.as_secs_f64() * 1000.0,
+            r.proof_size,
+            r.prove_memory.peak_heap as f64 / (1024.0 * 1024.0),
+            r.prove_memory
+                .peak_rss
+                .map_or("n/a".to_string(), |rss| format!("{:.2} MiB", rss as f64 / (1024.0 * 1024.0))),
+        );
+    }
+    results
+}
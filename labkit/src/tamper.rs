@@ -0,0 +1,64 @@
+//! Tampered-proof matrix for the rejection tests.
+//!
+//! A test serializes a valid proof and hands it to `assert_rejects_tampering` together
+//! with a valid proof of another statement and a verifier for serialized proofs. The
+//! verifier must accept the original and reject every variant: single bit flips spread
+//! over the proof, truncations, and the swapped-in proof. A verifier that panics on
+//! malformed bytes fails the test too, as would one that accepts them.
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+
+/// Variants of `proof` with one bit flipped: in the first and last byte and at a
+/// quarter, half and three quarters of the length. The flipped bit varies with the
+/// position.
+pub fn bit_flips(proof: &[u8]) -> Vec<(String, Vec<u8>)> {
+    let len = proof.len();
+    let mut positions = vec![0, len / 4, len / 2, 3 * len / 4, len - 1];
+    positions.dedup();
+    positions
+        .into_iter()
+        .enumerate()
+        .map(|(i, pos)| {
+            let bit = (i * 3) % 8;
+            let mut bytes = proof.to_vec();
+            bytes[pos] ^= 1 << bit;
+            (format!("bit {} of byte {} flipped", bit, pos), bytes)
+        })
+        .collect()
+}
+
+/// Prefixes of `proof`: empty, one byte, half and all but the last byte.
+pub fn truncations(proof: &[u8]) -> Vec<(String, Vec<u8>)> {
+    let len = proof.len();
+    let mut lengths = vec![0, 1, len / 2, len - 1];
+    lengths.dedup();
+    lengths
+        .into_iter()
+        .map(|n| (format!("proof truncated to {} of {} bytes", n, len), proof[..n].to_vec()))
+        .collect()
+}
+
+/// Asserts that `verify` accepts `proof` and rejects its bit flips, its truncations and
+/// `other`, a valid proof of a different statement. `verify` deserializes the bytes and
+/// checks them against the statement of `proof`.
+pub fn assert_rejects_tampering(proof: &[u8], other: &[u8], verify: impl Fn(&[u8]) -> bool) {
+    let accepts = |label: &str, bytes: &[u8]| match panic::catch_unwind(AssertUnwindSafe(|| verify(bytes))) {
+        Ok(accepted) => accepted,
+        Err(payload) => panic!("verifier panicked on {}: {}", label, panic_message(&*payload)),
+    };
+
+    assert!(accepts("the original proof", proof), "the original proof should verify");
+    for (label, bytes) in bit_flips(proof).into_iter().chain(truncations(proof)) {
+        assert!(!accepts(&label, &bytes), "{} was accepted", label);
+    }
+    assert!(!accepts("the proof of another statement", other), "the proof of another statement was accepted");
+}
+
+/// The message of a caught panic, for `panic!` and `assert!` payloads.
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    payload
+        .downcast_ref::<String>()
+        .map(String::as_str)
+        .or_else(|| payload.downcast_ref::<&str>().copied())
+        .unwrap_or("non-string panic payload")
+}
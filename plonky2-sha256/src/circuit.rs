@@ -402,13 +402,18 @@ pub fn make_circuits<F: RichField + Extendable<D>, const D: usize>(
 #[cfg(test)]
 mod tests {
     use anyhow::Result;
+    use plonky2::field::types::Field;
     use plonky2::iop::witness::{PartialWitness, WitnessWrite};
     use plonky2::plonk::circuit_builder::CircuitBuilder;
-    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::plonk::circuit_data::{CircuitConfig, CircuitData};
     use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use plonky2::plonk::proof::ProofWithPublicInputs;
     use rand::Rng;
 
-    use crate::circuit::{array_to_bits, make_circuits};
+    use crate::circuit::{array_to_bits, make_circuits, Sha256Targets};
+    use labkit::hiding::{self, Hiding};
+    use labkit::reference::{self, SHA256_MESSAGE};
+    use labkit::tamper;
 
     const EXPECTED_RES: [u8; 256] = [
         0, 0, 1, 1, 1, 1, 0, 0, 0, 1, 0, 1, 1, 1, 1, 0, 1, 0, 1, 0, 0, 0, 1, 1, 1, 1, 1, 0, 1, 0,
@@ -493,4 +498,71 @@ mod tests {
 
         data.verify(proof).expect("");
     }
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+
+    /// The circuit for `SHA256_MESSAGE`'s length with the digest bits as public inputs.
+    fn digest_circuit() -> (CircuitData<F, C, D>, Sha256Targets) {
+        let mut builder = CircuitBuilder::<F, D>::new(CircuitConfig::standard_recursion_config());
+        let targets = make_circuits(&mut builder, SHA256_MESSAGE.len() as u64 * 8);
+        for bit in &targets.digest {
+            builder.register_public_input(bit.target);
+        }
+        (builder.build::<C>(), targets)
+    }
+
+    fn prove(data: &CircuitData<F, C, D>, targets: &Sha256Targets, message: &[u8]) -> ProofWithPublicInputs<F, C, D> {
+        let mut pw = PartialWitness::new();
+        for (target, bit) in targets.message.iter().zip(array_to_bits(message)) {
+            pw.set_bool_target(*target, bit);
+        }
+        data.prove(pw).unwrap()
+    }
+
+    fn digest_bits(digest: &[u8; 32]) -> Vec<F> {
+        array_to_bits(digest).into_iter().map(F::from_bool).collect()
+    }
+
+    #[test]
+    fn test_digest_matches_reference() -> Result<()> {
+        let (data, targets) = digest_circuit();
+        let proof = prove(&data, &targets, SHA256_MESSAGE);
+        assert_eq!(proof.public_inputs, digest_bits(&reference::sha256(SHA256_MESSAGE)));
+
+        // The same proof claimed for another digest must not verify
+        for digest in reference::wrong_digests() {
+            let mut forged = proof.clone();
+            forged.public_inputs = digest_bits(&digest);
+            assert!(data.verify(forged).is_err());
+        }
+        data.verify(proof)
+    }
+
+    #[test]
+    fn test_tampered_proofs_are_rejected() {
+        let (data, targets) = digest_circuit();
+        let proof = prove(&data, &targets, SHA256_MESSAGE).to_bytes();
+        // Another message of the same length, so only the digest differs
+        let other = prove(&data, &targets, b"Hello, World?").to_bytes();
+
+        // The public inputs travel inside the proof, so the verifier compares them
+        // with the expected digest before checking the proof
+        let expected = digest_bits(&reference::sha256(SHA256_MESSAGE));
+        tamper::assert_rejects_tampering(&proof, &other, |bytes| {
+            ProofWithPublicInputs::<F, C, D>::from_bytes(bytes.to_vec(), &data.common)
+                .is_ok_and(|proof| proof.public_inputs == expected && data.verify(proof).is_ok())
+        });
+    }
+
+    #[test]
+    fn test_proofs_are_deterministic() {
+        // Flagged: the example and these tests use `standard_recursion_config`, which has
+        // no zero-knowledge, so the prover draws no randomness and the proof does not
+        // hide the message. `standard_recursion_zk_config` would randomize it.
+        let (data, targets) = digest_circuit();
+        let prove_bytes = || prove(&data, &targets, SHA256_MESSAGE).to_bytes();
+        assert_eq!(hiding::check(prove_bytes), Hiding::Deterministic);
+    }
 }
@@ -6,9 +6,22 @@ use plonky2::plonk::circuit_data::CircuitConfig;
 use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
 use plonky2::util::timing::TimingTree;
 use plonky2_sha256::circuit::{array_to_bits, make_circuits};
+use labkit::bench::{self, PhaseTimes};
+use labkit::bench::SizedRun;
+use labkit::input::{self, Sha256Input};
+use labkit::scaling;
+use labkit::memory;
+use labkit::rng;
 use sha2::{Digest, Sha256};
+use std::time::Instant;
 
-pub fn prove_sha256(msg: &[u8]) -> Result<()> {
+// Counts the heap usage that `memory::measure` reports
+#[global_allocator]
+static ALLOCATOR: labkit::memory::PeakAllocator = labkit::memory::PeakAllocator;
+
+/// Proves and verifies the SHA-256 of `msg`, returning the time of each phase
+/// together with the gate count, proof size and prover memory.
+pub fn prove_sha256(msg: &[u8]) -> Result<SizedRun> {
     let mut hasher = Sha256::new();
     hasher.update(msg);
     let hash = hasher.finalize();
@@ -37,25 +50,58 @@ pub fn prove_sha256(msg: &[u8]) -> Result<()> {
     }
 
     println!("Circuit statistics:");
-    println!("- Number of gates: {}", builder.num_gates());
+    let num_gates = builder.num_gates();
+    println!("- Number of gates: {}", num_gates);
     println!("- Number of public inputs: {}", builder.num_public_inputs());
 
-    let data = builder.build::<C>();
-    let proof = data.prove(pw).unwrap();
+    let start = Instant::now();
+    let (data, _) = memory::measure("Setup", || builder.build::<C>());
+    let setup_time = start.elapsed();
+    let start = Instant::now();
+    let (proof, prove_memory) = memory::measure("Prove", || data.prove(pw).unwrap());
+    let prove_time = start.elapsed();
+    println!("Prove time: {:.3} milliseconds", prove_time.as_secs_f64() * 1000.0);
     let proof_bytes = proof.to_bytes();
     let size = proof_bytes.len();
     println!("Size of proof_bytes: {}", size);
 
-    data.verify(proof)
+    let start = Instant::now();
+    let (result, _) = memory::measure("Verify", || data.verify(proof));
+    result?;
+    let verify_time = start.elapsed();
+    Ok(SizedRun {
+        size: num_gates,
+        times: PhaseTimes {
+            setup: setup_time,
+            prove: prove_time,
+            verify: verify_time,
+        },
+        proof_size: size,
+        prove_memory,
+    })
 }
 
 fn main() -> Result<()> {
+    rng::refuse_seed("the example uses no randomness, so every run already produces the same proof");
     // Initialize logging
     let mut builder = env_logger::Builder::from_default_env();
     builder.format_timestamp(None);
     builder.filter_level(LevelFilter::Debug);
     builder.try_init()?;
 
-    let msg = b"I love zk-sok";
-    prove_sha256(msg)
+    // `--input <file>` replaces the message, in the schema of snarkjs/sha256_*/input.json
+    let msg = input::from_args::<Sha256Input>().map_or(b"I love zk-sok".to_vec(), |input| input.message);
+    let msg = msg.as_slice();
+    if let Some(config) = bench::BenchConfig::from_args() {
+        bench::run("sha256", &config, || prove_sha256(msg).expect("proving failed").times);
+    } else if let Some(lengths) = scaling::input_lengths_from_args() {
+        scaling::input_size_sweep("sha256", "gates", &lengths, |len| {
+            prove_sha256(&scaling::sample_message(len)).expect("proving failed")
+        });
+    } else if let Some(counts) = scaling::thread_counts_from_args() {
+        scaling::sweep("sha256", &counts, || prove_sha256(msg).expect("proving failed").times.prove);
+    } else {
+        prove_sha256(msg)?;
+    }
+    Ok(())
 }
@@ -0,0 +1,36 @@
+//! Arbitrary bytes into `ProofWithPublicInputs::from_bytes` for the range_proof circuit,
+//! and every proof that decodes into `CircuitData::verify`. Neither may panic; a proof
+//! with the wrong shape must fail to decode or fail verification.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2::plonk::circuit_data::{CircuitConfig, CircuitData};
+use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+use plonky2::plonk::proof::ProofWithPublicInputs;
+
+const D: usize = 2;
+type C = PoseidonGoldilocksConfig;
+type F = <C as GenericConfig<D>>::F;
+
+/// Same circuit as `build_circuit` in the range_proof example, so its saved proofs
+/// decode against the common data.
+fn build_circuit() -> CircuitData<F, C, D> {
+    let mut builder = CircuitBuilder::<F, D>::new(CircuitConfig::standard_recursion_zk_config());
+    let value = builder.add_virtual_target();
+    builder.register_public_input(value);
+    builder.range_check(value, 32);
+    builder.build::<C>()
+}
+
+thread_local! {
+    static CIRCUIT: CircuitData<F, C, D> = build_circuit();
+}
+
+fuzz_target!(|bytes: &[u8]| {
+    CIRCUIT.with(|data| {
+        if let Ok(proof) = ProofWithPublicInputs::<F, C, D>::from_bytes(bytes.to_vec(), &data.common) {
+            let _ = data.verify(proof);
+        }
+    });
+});
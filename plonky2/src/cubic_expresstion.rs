@@ -1,42 +1,79 @@
 use anyhow::Result;
 use plonky2::field::types::Field;
+use plonky2::iop::target::Target;
 use plonky2::iop::witness::{PartialWitness, WitnessWrite};
 use plonky2::plonk::circuit_builder::CircuitBuilder;
 use plonky2::plonk::circuit_data::CircuitConfig;
 use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+use labkit::bench::{self, PhaseTimes};
+use labkit::input::{self, CubicInput};
+use labkit::scaling;
+use labkit::memory;
+use labkit::rng;
 use std::time::Instant;
 
+// Counts the heap usage that `memory::measure` reports
+#[global_allocator]
+static ALLOCATOR: labkit::memory::PeakAllocator = labkit::memory::PeakAllocator;
+
 /// An example of using Plonky2 to prove a statement of the form
-/// "I know x² - 4x + 7".
+/// "I know x such that x³ + x + 5 = y".
 fn main() -> Result<()> {
-    const D: usize = 2;
-    type C = PoseidonGoldilocksConfig;
-    type F = <C as GenericConfig<D>>::F;
+    rng::refuse_seed("plonky2 samples the zero-knowledge blinding itself, so a seed could not fix its proof");
+    if let Some(config) = bench::BenchConfig::from_args() {
+        bench::run("cubic_expression", &config, || prove_and_verify().expect("proving failed"));
+    } else if let Some(counts) = scaling::thread_counts_from_args() {
+        scaling::sweep("cubic_expression", &counts, || prove_and_verify().expect("proving failed").prove);
+    } else {
+        prove_and_verify()?;
+    }
+    Ok(())
+}
+
+const D: usize = 2;
+type C = PoseidonGoldilocksConfig;
+type F = <C as GenericConfig<D>>::F;
+
+/// Builds the circuit for `x^3 + x + 5` and returns it with the targets of `x` and the result.
+fn build_circuit() -> (CircuitBuilder<F, D>, Target, Target) {
+    build_circuit_with(CircuitConfig::standard_recursion_zk_config())
+}
 
-    let config = CircuitConfig::standard_recursion_zk_config();
+/// `build_circuit` under `config`.
+fn build_circuit_with(config: CircuitConfig) -> (CircuitBuilder<F, D>, Target, Target) {
     let mut builder = CircuitBuilder::<F, D>::new(config);
 
-    // The arithmetic circuit.
+    // The arithmetic circuit. The constant 5 is the one of the snarkjs circuit and of
+    // `labkit::reference::cubic`.
     let x = builder.add_virtual_target();
     let a = builder.mul(x, x);
     let b = builder.mul(a, x);
     let d = builder.add(b, x);
-    let e = builder.add_const(d, F::from_canonical_u32(1));
+    let e = builder.add_const(d, F::from_canonical_u32(5));
 
     // Public inputs are the initial value (provided below) and the result (which is generated).
     builder.register_public_input(x);
     builder.register_public_input(e);
+    (builder, x, e)
+}
+
+/// Builds, proves and verifies the circuit, returning the time of each phase.
+fn prove_and_verify() -> Result<PhaseTimes> {
+    let (builder, x, _) = build_circuit();
     let mut pw = PartialWitness::new();
-    pw.set_target(x, F::from_canonical_u32(3));
+    // x = 3, unless `--input <file>` gives another x in the schema of snarkjs/cubic_*/input.json
+    let x_value = input::from_args::<CubicInput>().map_or(3, |input| input.x);
+    pw.set_target(x, F::from_noncanonical_u64(x_value))?;
     println!(
         "Constructing inner proof with {} gates",
         builder.num_gates()
     );
-    let data = builder.build::<C>();
-
+    let start0 = Instant::now();
+    let (data, _) = memory::measure("Setup", || builder.build::<C>());
 
     let start1 = Instant::now();
-    let proof = data.prove(pw)?;
+    let (proof, _) = memory::measure("Prove", || data.prove(pw));
+    let proof = proof?;
     let start2 = Instant::now();
 
     let proof_bytes = proof.to_bytes();
@@ -46,11 +83,12 @@ fn main() -> Result<()> {
     println!("Size of proof_bytes: {}", size);
 
     println!(
-        "x3 +x + 1 where x = {} is {}",
+        "x^3 + x + 5 where x = {} is {}",
         proof.public_inputs[0],
         proof.public_inputs[1]
     );
-    data.verify(proof);
+    let (verify_result, _) = memory::measure("Verify", || data.verify(proof));
+    verify_result?;
     let start3 = Instant::now();
     let duration1 = start2.duration_since(start1);
     let duration2 = start3.duration_since(start2);
@@ -59,5 +97,102 @@ fn main() -> Result<()> {
     let millis2 = duration2.as_secs_f64() * 1000.0;
     println!("Prove time: {:.3} milliseconds", millis1);
     println!("Verify time: {:.3} milliseconds", millis2);
-    Ok(())
-}
\ No newline at end of file
+    Ok(PhaseTimes {
+        setup: start1.duration_since(start0),
+        prove: duration1,
+        verify: duration2,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use plonky2::plonk::proof::ProofWithPublicInputs;
+    use labkit::hiding::{self, Hiding};
+    use labkit::reference::{self, CUBIC_X};
+    use labkit::tamper;
+
+    #[test]
+    fn test_output_matches_reference() -> Result<()> {
+        let (builder, x, _) = build_circuit();
+        let data = builder.build::<C>();
+        let mut pw = PartialWitness::new();
+        pw.set_target(x, F::from_canonical_u64(CUBIC_X))?;
+        let proof = data.prove(pw)?;
+        assert_eq!(proof.public_inputs[1], F::from_canonical_u64(reference::cubic(CUBIC_X)));
+        data.verify(proof)
+    }
+
+    #[test]
+    fn test_wrong_outputs_are_rejected() -> Result<()> {
+        let (builder, x, _) = build_circuit();
+        let data = builder.build::<C>();
+        let mut pw = PartialWitness::new();
+        pw.set_target(x, F::from_canonical_u64(CUBIC_X))?;
+        let proof = data.prove(pw)?;
+
+        for y in reference::wrong_cubic_outputs() {
+            // An honest proof checked against a wrong output
+            let mut forged = proof.clone();
+            forged.public_inputs[1] = F::from_canonical_u64(y);
+            assert!(data.verify(forged).is_err());
+
+            // A witness assigning a wrong output: witness generation computes the
+            // output from x and finds it already set to y
+            let (builder, x, e) = build_circuit();
+            let data = builder.build::<C>();
+            let mut pw = PartialWitness::new();
+            pw.set_target(x, F::from_canonical_u64(CUBIC_X))?;
+            pw.set_target(e, F::from_canonical_u64(y))?;
+            let err = data.prove(pw).expect_err("a wrong output should not be proven");
+            assert!(
+                format!("{:#}", err).contains("was set twice with different values"),
+                "y = {} was rejected with an unexpected error: {:#}",
+                y,
+                err
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_tampered_proofs_are_rejected() {
+        let (builder, x, _) = build_circuit();
+        let data = builder.build::<C>();
+        let prove = |value: u64| {
+            let mut pw = PartialWitness::new();
+            pw.set_target(x, F::from_canonical_u64(value)).unwrap();
+            data.prove(pw).unwrap().to_bytes()
+        };
+        let proof = prove(CUBIC_X);
+        let other = prove(CUBIC_X + 1);
+
+        // The public inputs travel inside the proof, so the verifier compares them with
+        // the statement before checking the proof
+        let statement = [F::from_canonical_u64(CUBIC_X), F::from_canonical_u64(reference::cubic(CUBIC_X))];
+        tamper::assert_rejects_tampering(&proof, &other, |bytes| {
+            ProofWithPublicInputs::<F, C, D>::from_bytes(bytes.to_vec(), &data.common)
+                .is_ok_and(|proof| proof.public_inputs == statement && data.verify(proof).is_ok())
+        });
+    }
+
+    /// Whether two proofs of `x = CUBIC_X` differ under `config`.
+    fn hiding_under(config: CircuitConfig) -> Hiding {
+        let (builder, x, _) = build_circuit_with(config);
+        let data = builder.build::<C>();
+        hiding::check(|| {
+            let mut pw = PartialWitness::new();
+            pw.set_target(x, F::from_canonical_u64(CUBIC_X)).unwrap();
+            data.prove(pw).unwrap().to_bytes()
+        })
+    }
+
+    #[test]
+    fn test_proofs_are_randomized() {
+        // The zk config blinds the wire polynomials and salts the FRI leaves
+        assert_eq!(hiding_under(CircuitConfig::standard_recursion_zk_config()), Hiding::Randomized);
+        // Without zk the prover draws no randomness, so equal witnesses give equal
+        // proofs: `standard_recursion_config` does not hide the witness
+        assert_eq!(hiding_under(CircuitConfig::standard_recursion_config()), Hiding::Deterministic);
+    }
+}
@@ -0,0 +1,120 @@
+use anyhow::Result;
+use plonky2::field::types::{Field, Sample};
+use plonky2::iop::target::Target;
+use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2::plonk::circuit_data::CircuitConfig;
+use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+use labkit::bench::{PhaseTimes, SizedRun};
+use labkit::rng::{self, ExampleRng};
+use labkit::ladder;
+use labkit::memory;
+use std::time::Instant;
+
+// Counts the heap usage that `memory::measure` reports
+#[global_allocator]
+static ALLOCATOR: labkit::memory::PeakAllocator = labkit::memory::PeakAllocator;
+
+/// Chains `x_{i+1} = x_i^3 + x_i + 5` for a growing number of steps, with `x_0`
+/// private and `x_steps` public, and reports how the prover scales.
+fn main() -> Result<()> {
+    rng::refuse_seed("plonky2 samples the zero-knowledge blinding itself, so a seed could not fix its proof");
+    let log_sizes = ladder::log_sizes_from_args();
+    ladder::sweep("cubic_ladder", "gates", &log_sizes, |steps| {
+        prove_and_verify(steps).expect("proving failed")
+    });
+    Ok(())
+}
+
+const D: usize = 2;
+type C = PoseidonGoldilocksConfig;
+type F = <C as GenericConfig<D>>::F;
+
+/// Builds a ladder of `steps` steps and returns it with the target of `x_0`.
+fn build_circuit(steps: usize) -> (CircuitBuilder<F, D>, Target) {
+    let config = CircuitConfig::standard_recursion_zk_config();
+    let mut builder = CircuitBuilder::<F, D>::new(config);
+
+    let x0 = builder.add_virtual_target();
+    let mut x = x0;
+    for _ in 0..steps {
+        let x_squared = builder.mul(x, x);
+        let x_cubed = builder.mul(x_squared, x);
+        let sum = builder.add(x_cubed, x);
+        x = builder.add_const(sum, F::from_canonical_u64(ladder::STEP_CONSTANT));
+    }
+    builder.register_public_input(x);
+    (builder, x0)
+}
+
+/// Computes `x_steps` natively.
+fn ladder_output(x0: F, steps: usize) -> F {
+    (0..steps).fold(x0, |x, _| x * x * x + x + F::from_canonical_u64(ladder::STEP_CONSTANT))
+}
+
+/// Builds, proves and verifies a ladder of `steps` steps.
+fn prove_and_verify(steps: usize) -> Result<SizedRun> {
+    let (builder, x0) = build_circuit(steps);
+
+    let x0_value = F::sample(&mut ExampleRng::new(None));
+    let mut pw = PartialWitness::new();
+    pw.set_target(x0, x0_value)?;
+
+    let num_gates = builder.num_gates();
+    println!("Number of gates: {}", num_gates);
+
+    let start0 = Instant::now();
+    let (data, _) = memory::measure("Setup", || builder.build::<C>());
+    let start1 = Instant::now();
+    let (proof, prove_memory) = memory::measure("Prove", || data.prove(pw));
+    let proof = proof?;
+    let start2 = Instant::now();
+
+    let proof_size = proof.to_bytes().len();
+    println!("Size of proof_bytes: {}", proof_size);
+    assert_eq!(
+        proof.public_inputs[0],
+        ladder_output(x0_value, steps),
+        "public output should match the native ladder"
+    );
+
+    let (verify_result, _) = memory::measure("Verify", || data.verify(proof));
+    verify_result?;
+    let start3 = Instant::now();
+
+    Ok(SizedRun {
+        size: num_gates,
+        times: PhaseTimes {
+            setup: start1.duration_since(start0),
+            prove: start2.duration_since(start1),
+            verify: start3.duration_since(start2),
+        },
+        proof_size,
+        prove_memory,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ladder_proves_its_output() -> Result<()> {
+        let steps = ladder::steps(4);
+        let (builder, x0) = build_circuit(steps);
+        let data = builder.build::<C>();
+        let mut pw = PartialWitness::new();
+        pw.set_target(x0, F::from_canonical_u64(3))?;
+        let proof = data.prove(pw)?;
+
+        let y = ladder_output(F::from_canonical_u64(3), steps);
+        assert_eq!(proof.public_inputs, [y]);
+        data.verify(proof.clone())?;
+
+        // The honest proof checked against a wrong x_N
+        let mut forged = proof;
+        forged.public_inputs[0] = y + F::ONE;
+        assert!(data.verify(forged).is_err(), "a wrong x_N should be rejected");
+        Ok(())
+    }
+}
@@ -1,17 +1,44 @@
 use anyhow::Result;
-use plonky2::field::types::Field;
+use plonky2::field::types::{Field, Field64};
+use plonky2::iop::target::Target;
 use plonky2::iop::witness::{PartialWitness, WitnessWrite};
 use plonky2::plonk::circuit_builder::CircuitBuilder;
 use plonky2::plonk::circuit_data::CircuitConfig;
 use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+use labkit::bench::{self, PhaseTimes};
+use labkit::input::{self, RangeInput};
+use labkit::corpus;
+use labkit::scaling;
+use labkit::memory;
+use labkit::rng;
 use std::time::Instant;
 
+// Counts the heap usage that `memory::measure` reports
+#[global_allocator]
+static ALLOCATOR: labkit::memory::PeakAllocator = labkit::memory::PeakAllocator;
+
 /// An example of using Plonky2 to prove that a given value lies in a given range.
 fn main() -> Result<()> {
-    const D: usize = 2;
-    type C = PoseidonGoldilocksConfig;
-    type F = <C as GenericConfig<D>>::F;
+    rng::refuse_seed("plonky2 samples the zero-knowledge blinding itself, so a seed could not fix its proof");
+    if let Some(config) = bench::BenchConfig::from_args() {
+        bench::run("range_proof", &config, || prove_and_verify().expect("proving failed"));
+    } else if let Some(counts) = scaling::thread_counts_from_args() {
+        scaling::sweep("range_proof", &counts, || prove_and_verify().expect("proving failed").prove);
+    } else {
+        prove_and_verify()?;
+    }
+    Ok(())
+}
+
+const D: usize = 2;
+type C = PoseidonGoldilocksConfig;
+type F = <C as GenericConfig<D>>::F;
+
+/// Number of bits the value is checked against.
+const LOG_MAX: usize = 32;
 
+/// Builds the range check circuit and returns it with the target of the value.
+fn build_circuit() -> (CircuitBuilder<F, D>, Target) {
     let config = CircuitConfig::standard_recursion_zk_config(); // Or use standard_recursion_zk_config
     let mut builder = CircuitBuilder::<F, D>::new(config);
 
@@ -21,41 +48,59 @@ fn main() -> Result<()> {
     // Registered as a public input (even though it's secret) so we can print out the value later.
     builder.register_public_input(value);
 
-    let log_max = 32;
     println!("Gates before range check: {}", builder.num_gates());
-    builder.range_check(value, log_max);
+    builder.range_check(value, LOG_MAX);
     println!("Gates after range check: {}", builder.num_gates());
+    (builder, value)
+}
+
+/// Builds, proves and verifies the circuit, returning the time of each phase.
+fn prove_and_verify() -> Result<PhaseTimes> {
+    let (builder, value) = build_circuit();
 
     // Add debug information
     println!("Number of gates before building: {}", builder.num_gates());
     println!("Number of public inputs: {}", builder.num_public_inputs());
 
     let mut pw = PartialWitness::new();
-    let _ = pw.set_target(value, F::from_canonical_usize(10086));
+    // 10086, unless `--input <file>` gives another value in the schema of
+    // snarkjs/rangeproof_*/input.json
+    let value_input = input::from_args::<RangeInput>().map_or(10086, |input| {
+        assert_eq!(input.bits, LOG_MAX, "the circuit proves {}-bit ranges", LOG_MAX);
+        // Larger values would wrap around the field and could pass the range check
+        assert!(input.value < F::ORDER, "the value must be below the Goldilocks modulus");
+        input.value
+    });
+    pw.set_target(value, F::from_canonical_u64(value_input))?;
 
     println!(
         "Constructing inner proof with {} gates",
         builder.num_gates()
     );
     
-    let start1 = Instant::now();
+    let start0 = Instant::now();
     // Build circuit data
-    let data = builder.build::<C>();
+    let (data, _) = memory::measure("Setup", || builder.build::<C>());
+    let start1 = Instant::now();
     // Generate proof
-    let proof = data.prove(pw)?;
+    let (proof, _) = memory::measure("Prove", || data.prove(pw));
+    let proof = proof?;
     let start2 = Instant::now();
     
     // Convert proof to byte array
     let proof_bytes = proof.to_bytes();
     let size = proof_bytes.len();
     println!("Size of proof_bytes: {}", size);
+    // `--save-corpus <dir>`: seed the fuzz target with this proof
+    corpus::save_from_args("proof_from_bytes", "range_proof", &proof_bytes);
 
     println!(
         "Value {} is less than 2^{}",
-        proof.public_inputs[0], log_max,
+        proof.public_inputs[0], LOG_MAX,
     );
 
-    data.verify(proof);
+    let (verify_result, _) = memory::measure("Verify", || data.verify(proof));
+    verify_result?;
     let start3 = Instant::now();
     let duration1 = start2.duration_since(start1);
     let duration2 = start3.duration_since(start2);
@@ -64,5 +109,68 @@ fn main() -> Result<()> {
     let millis2 = duration2.as_secs_f64() * 1000.0;
     println!("Prove time: {:.3} milliseconds", millis1);
     println!("Verify time: {:.3} milliseconds", millis2);
-    Ok(())
+    Ok(PhaseTimes {
+        setup: start1.duration_since(start0),
+        prove: duration1,
+        verify: duration2,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use plonky2::plonk::proof::ProofWithPublicInputs;
+    use labkit::reference::{self, RANGE_BITS, RANGE_VALUE};
+    use labkit::tamper;
+
+    /// Proves that `value` is in range and verifies the proof.
+    fn prove_and_check(value: u64) -> Result<()> {
+        let (builder, target) = build_circuit();
+        let data = builder.build::<C>();
+        let mut pw = PartialWitness::new();
+        pw.set_target(target, F::from_canonical_u64(value))?;
+        data.verify(data.prove(pw)?)
+    }
+
+    #[test]
+    fn test_value_in_range_is_accepted() -> Result<()> {
+        assert_eq!(LOG_MAX, RANGE_BITS);
+        prove_and_check(RANGE_VALUE)
+    }
+
+    #[test]
+    fn test_out_of_range_values_are_rejected() {
+        for value in reference::OUT_OF_RANGE_VALUES {
+            // `range_check` splits the value into bits and ties those above LOG_MAX to
+            // zero, so witness generation sets the zero wire to a nonzero bit
+            let err = prove_and_check(value).expect_err("an out-of-range value should not be proven");
+            assert!(
+                format!("{:#}", err).contains("was set twice with different values"),
+                "{} was rejected with an unexpected error: {:#}",
+                value,
+                err
+            );
+        }
+    }
+
+    #[test]
+    fn test_tampered_proofs_are_rejected() {
+        let (builder, target) = build_circuit();
+        let data = builder.build::<C>();
+        let prove = |value: u64| {
+            let mut pw = PartialWitness::new();
+            pw.set_target(target, F::from_canonical_u64(value)).unwrap();
+            data.prove(pw).unwrap().to_bytes()
+        };
+        let proof = prove(RANGE_VALUE);
+        let other = prove(RANGE_VALUE + 1);
+
+        // The public inputs travel inside the proof, so the verifier compares them with
+        // the statement before checking the proof
+        let statement = [F::from_canonical_u64(RANGE_VALUE)];
+        tamper::assert_rejects_tampering(&proof, &other, |bytes| {
+            ProofWithPublicInputs::<F, C, D>::from_bytes(bytes.to_vec(), &data.common)
+                .is_ok_and(|proof| proof.public_inputs == statement && data.verify(proof).is_ok())
+        });
+    }
 }
@@ -0,0 +1,28 @@
+//! Arbitrary bytes into `bincode::deserialize::<SNARK>` and every proof that decodes into
+//! `SNARK::verify`, through the verifier the tamper tests use. The statement is the
+//! range proof of `RANGE_VALUE` in 32 bits under the rangeproof example's domain, so
+//! `rangeproof --value 1234567890 --mode snark --save-corpus fuzz/corpus` seeds it with a
+//! proof that verifies. Bad length prefixes and malformed group elements must be
+//! rejected, never panic. libspartan 0.9's verifier checks some claims with `assert_eq!`,
+//! so bytes that reach those checks crash the target; see the SNARK tamper tests.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use labkit::context::ProofContext;
+use spartan_lab::mode::{self, EncodedVerifier, Mode};
+use spartan_lab::range;
+use labkit::reference::{RANGE_BITS, RANGE_VALUE};
+
+thread_local! {
+    static VERIFY: EncodedVerifier = {
+        let r1cs = range::range_r1cs(RANGE_VALUE, RANGE_BITS).expect("the reference value is in range");
+        let (_, verify) = mode::prove_encoded(Mode::Snark, r1cs, &ProofContext::new(b"rangeproof_example", Vec::new()));
+        verify
+    };
+}
+
+fuzz_target!(|data: &[u8]| {
+    VERIFY.with(|verify| {
+        let _ = verify(data);
+    });
+});
@@ -8,160 +8,139 @@
 //! `(Z3 + 5) * 1 - I0 = 0`
 //!
 //! [here]: https://medium.com/@VitalikButerin/quadratic-arithmetic-programs-from-zero-to-hero-f6d558cea649
-#![allow(clippy::assertions_on_result_states)]
-use std::time::Instant;
-
 use curve25519_dalek::scalar::Scalar;
-use libspartan::{InputsAssignment, Instance, SNARKGens, VarsAssignment, SNARK};
-use merlin::Transcript;
-use rand::rngs::OsRng;
-use serde::Serialize;
-use bincode;
-
-#[allow(non_snake_case)]
-fn produce_r1cs() -> (
-  usize,
-  usize,
-  usize,
-  usize,
-  Instance,
-  VarsAssignment,
-  InputsAssignment,
-) {
-  // parameters of the R1CS instance
-  let num_cons = 4;
-  let num_vars = 4;
-  let num_inputs = 1;
-  let num_non_zero_entries = 8;
-
-  // We will encode the above constraints into three matrices, where
-  // the coefficients in the matrix are in the little-endian byte order
-  let mut A: Vec<(usize, usize, [u8; 32])> = Vec::new();
-  let mut B: Vec<(usize, usize, [u8; 32])> = Vec::new();
-  let mut C: Vec<(usize, usize, [u8; 32])> = Vec::new();
-
-  let one = Scalar::ONE.to_bytes();
-
-  // R1CS is a set of three sparse matrices A B C, where is a row for every
-  // constraint and a column for every entry in z = (vars, 1, inputs)
-  // An R1CS instance is satisfiable iff:
-  // Az \circ Bz = Cz, where z = (vars, 1, inputs)
-
-  // constraint 0 entries in (A,B,C)
-  // constraint 0 is Z0 * Z0 - Z1 = 0.
-  A.push((0, 0, one));
-  B.push((0, 0, one));
-  C.push((0, 1, one));
-
-  // constraint 1 entries in (A,B,C)
-  // constraint 1 is Z1 * Z0 - Z2 = 0.
-  A.push((1, 1, one));
-  B.push((1, 0, one));
-  C.push((1, 2, one));
-
-  // constraint 2 entries in (A,B,C)
-  // constraint 2 is (Z2 + Z0) * 1 - Z3 = 0.
-  A.push((2, 2, one));
-  A.push((2, 0, one));
-  B.push((2, num_vars, one));
-  C.push((2, 3, one));
-
-  // constraint 3 entries in (A,B,C)
-  // constraint 3 is (Z3 + 5) * 1 - I0 = 0.
-  A.push((3, 3, one));
-  A.push((3, num_vars, Scalar::from(5u32).to_bytes()));
-  B.push((3, num_vars, one));
-  C.push((3, num_vars + 1, one));
-
-  let r1cs_file = "sha256.r1cs";
-
-  //let instance = Instance::from_file(r1cs_file).expect("Failed to load R1CS");
-
-  let inst = Instance::new(num_cons, num_vars, num_inputs, &A, &B, &C).unwrap();
-
-  // compute a satisfying assignment
-  let mut csprng: OsRng = OsRng;
-  let z0 = Scalar::random(&mut csprng);
-  let z1 = z0 * z0; // constraint 0
-  let z2 = z1 * z0; // constraint 1
-  let z3 = z2 + z0; // constraint 2
-  let i0 = z3 + Scalar::from(5u32); // constraint 3
-
-  // create a VarsAssignment
-  let mut vars = vec![Scalar::ZERO.to_bytes(); num_vars];
-  vars[0] = z0.to_bytes();
-  vars[1] = z1.to_bytes();
-  vars[2] = z2.to_bytes();
-  vars[3] = z3.to_bytes();
-  let assignment_vars = VarsAssignment::new(&vars).unwrap();
-
-  // create an InputsAssignment
-  let mut inputs = vec![Scalar::ZERO.to_bytes(); num_inputs];
-  inputs[0] = i0.to_bytes();
-  let assignment_inputs = InputsAssignment::new(&inputs).unwrap();
-
-  // check if the instance we created is satisfiable
-  let res = inst.is_sat(&assignment_vars, &assignment_inputs);
-  assert!(res.unwrap(), "should be satisfied");
+use labkit::bench;
+use labkit::input::{self, CubicInput};
+use spartan_lab::mode;
+use spartan_lab::r1cs::{R1cs, R1csBuilder};
+use labkit::rng::ExampleRng;
+use labkit::scaling;
+
+// Counts the heap usage that `memory::measure` reports
+#[global_allocator]
+static ALLOCATOR: labkit::memory::PeakAllocator = labkit::memory::PeakAllocator;
+
+/// Constrains `x^3 + x + 5 = y` with `x` as the witness and `y` as the public input.
+fn cubic_builder(x: Scalar, y: Scalar) -> R1csBuilder {
+  let mut cs = R1csBuilder::new();
+  let z0 = cs.alloc(x);
+  let z1 = cs.alloc(x * x);
+  let z2 = cs.alloc(x * x * x);
+  let z3 = cs.alloc(x * x * x + x);
+  let i0 = cs.alloc_input(y);
+
+  cs.constrain(z0, z0, z1);
+  cs.constrain(z1, z0, z2);
+  cs.constrain(z2 + z0, Scalar::ONE, z3);
+  cs.constrain(z3 + Scalar::from(5u32), Scalar::ONE, i0);
+  cs
+}
 
-  (
-    num_cons,
-    num_vars,
-    num_inputs,
-    num_non_zero_entries,
-    inst,
-    assignment_vars,
-    assignment_inputs,
-  )
+fn produce_r1cs() -> R1cs {
+  // compute a satisfying assignment, for an `x` from `--input <file>` (the schema of
+  // snarkjs/cubic_*/input.json), or else from the OS
+  let x = match input::from_args::<CubicInput>() {
+    Some(input) => Scalar::from(input.x),
+    None => Scalar::random(&mut ExampleRng::new(None)),
+  };
+  let cs = cubic_builder(x, x * x * x + x + Scalar::from(5u32));
+  assert_eq!(cs.first_unsatisfied(), None, "should be satisfied");
+
+  cs.build()
 }
 
 fn main() {
-  // produce an R1CS instance
-  let (
-    num_cons,
-    num_vars,
-    num_inputs,
-    num_non_zero_entries,
-    inst,
-    assignment_vars,
-    assignment_inputs,
-  ) = produce_r1cs();
-
-  // produce public parameters
-  let gens = SNARKGens::new(num_cons, num_vars, num_inputs, num_non_zero_entries);
-
-  // create a commitment to the R1CS instance
-  let (comm, decomm) = SNARK::encode(&inst, &gens);
-
-  // produce a proof of satisfiability
-  let mut prover_transcript = Transcript::new(b"snark_example");
-  let start1 = Instant::now();
-  let proof = SNARK::prove(
-    &inst,
-    &comm,
-    &decomm,
-    assignment_vars,
-    &assignment_inputs,
-    &gens,
-    &mut prover_transcript,
-  );
-  let start2 = Instant::now();
-  
-  let serialized_proof = bincode::serialize(&proof).expect("Serialization failed");
-  println!("Serialized proof size: {} bytes", serialized_proof.len());
-
-  // verify the proof of satisfiability
-  let mut verifier_transcript = Transcript::new(b"snark_example");
-  assert!(proof
-    .verify(&comm, &assignment_inputs, &mut verifier_transcript, &gens)
-    .is_ok());
-  let start3 = Instant::now();
-  println!("proof verification successful!");
-  let duration1 = start2.duration_since(start1);
-    let duration2 = start3.duration_since(start2);
+  let modes = mode::modes_from_args();
+  let options = mode::Options::from_args(b"snark_example");
+  if let Some(config) = bench::BenchConfig::from_args() {
+    for m in modes {
+      bench::run(&format!("cubic_{}", m.name()), &config, || {
+        mode::prove_and_verify(m, "cubic", produce_r1cs(), &options).run.times
+      });
+    }
+  } else if let Some(counts) = scaling::thread_counts_from_args() {
+    for m in modes {
+      scaling::sweep(&format!("cubic_{}", m.name()), &counts, || {
+        mode::prove_and_verify(m, "cubic", produce_r1cs(), &options).run.times.prove
+      });
+    }
+  } else {
+    let runs: Vec<_> = modes
+      .into_iter()
+      .map(|m| mode::prove_and_verify(m, "cubic", produce_r1cs(), &options))
+      .collect();
+    mode::print_comparison("cubic", &runs);
+  }
+}
 
-    let millis1 = duration1.as_secs_f64() * 1000.0;
-    let millis2 = duration2.as_secs_f64() * 1000.0;
-    println!("Prove time: {:.3} milliseconds", millis1);
-    println!("Verify time: {:.3} milliseconds", millis2);
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use libspartan::{NIZKGens, NIZK};
+  use labkit::context::ProofContext;
+  use labkit::hiding::{self, Hiding};
+  use labkit::reference::{self, CUBIC_X};
+  use labkit::tamper;
+
+  fn reference_r1cs(y: u64) -> R1cs {
+    cubic_builder(Scalar::from(CUBIC_X), Scalar::from(y)).build()
+  }
+
+  #[test]
+  fn test_output_matches_reference() {
+    let cs = cubic_builder(Scalar::from(CUBIC_X), Scalar::from(reference::cubic(CUBIC_X)));
+    assert_eq!(cs.first_unsatisfied(), None);
+    let r1cs = cs.build();
+    assert!(r1cs.inst.is_sat(&r1cs.vars, &r1cs.inputs).unwrap());
+  }
+
+  #[test]
+  fn test_wrong_outputs_are_rejected() {
+    let r1cs = reference_r1cs(reference::cubic(CUBIC_X));
+    let gens = NIZKGens::new(r1cs.num_cons, r1cs.num_vars, r1cs.num_inputs);
+    let context = ProofContext::new(b"snark_example", "");
+    let proof = NIZK::prove(&r1cs.inst, r1cs.vars, &r1cs.inputs, &gens, &mut context.transcript());
+
+    for y in reference::wrong_cubic_outputs() {
+      let wrong = reference_r1cs(y);
+      assert!(!wrong.inst.is_sat(&wrong.vars, &wrong.inputs).unwrap(), "y = {} should be rejected", y);
+      // The honest proof checked against the wrong output
+      assert!(proof
+        .verify(&r1cs.inst, &wrong.inputs, &mut context.transcript(), &gens)
+        .is_err());
+    }
+  }
+
+  /// Runs the tamper matrix on `m` proofs for x = `CUBIC_X`, with a proof for the next
+  /// x swapped in.
+  fn assert_rejects_tampering(m: mode::Mode) {
+    let context = ProofContext::new(b"snark_example", "");
+    let (proof, verify) = mode::prove_encoded(m, reference_r1cs(reference::cubic(CUBIC_X)), &context);
+    let other_r1cs = cubic_builder(Scalar::from(CUBIC_X + 1), Scalar::from(reference::cubic(CUBIC_X + 1))).build();
+    let (other, _) = mode::prove_encoded(m, other_r1cs, &context);
+    tamper::assert_rejects_tampering(&proof, &other, verify);
+  }
+
+  #[test]
+  fn test_tampered_nizk_proofs_are_rejected() {
+    assert_rejects_tampering(mode::Mode::Nizk);
+  }
+
+  // The libspartan 0.9 bug described at the range proof's SNARK tamper test: the
+  // bit flipped in the middle of the proof fails an `assert_eq!` in `SNARK::verify`.
+  #[test]
+  #[should_panic(expected = "assertion `left == right` failed")]
+  fn test_tampered_snark_proofs_panic_the_verifier() {
+    assert_rejects_tampering(mode::Mode::Snark);
+  }
+
+  #[test]
+  fn test_proofs_are_randomized() {
+    let context = ProofContext::new(b"snark_example", "");
+    for m in mode::Mode::ALL {
+      // Both modes seed the prover's random tape from `OsRng`
+      let prove = || mode::prove_encoded(m, reference_r1cs(reference::cubic(CUBIC_X)), &context).0;
+      assert_eq!(hiding::check(prove), Hiding::Randomized, "{} proofs should differ", m.name());
+    }
+  }
 }
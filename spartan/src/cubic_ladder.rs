@@ -0,0 +1,86 @@
+//! Chains the cubic_expression statement `steps` times: `x_{i+1} = x_i^3 + x_i + 5`.
+//!
+//! The R1CS has two constraints per step, with `z = (x_0, s_0, ..., x_{N-1}, s_{N-1}, 1, x_N)`:
+//! `x_i * x_i - s_i = 0`
+//! `s_i * x_i - (x_{i+1} - x_i - 5) = 0`
+//! where `x_N` is the only public input.
+use curve25519_dalek::scalar::Scalar;
+use labkit::ladder;
+use spartan_lab::mode;
+use spartan_lab::r1cs::{R1cs, R1csBuilder};
+use labkit::rng::ExampleRng;
+
+// Counts the heap usage that `memory::measure` reports
+#[global_allocator]
+static ALLOCATOR: labkit::memory::PeakAllocator = labkit::memory::PeakAllocator;
+
+/// An R1CS instance for a ladder of `steps` steps, together with a satisfying
+/// assignment for a random `x_0` drawn from the OS.
+fn produce_r1cs(steps: usize) -> R1cs {
+  ladder_r1cs(Scalar::random(&mut ExampleRng::new(None)), steps)
+}
+
+/// The ladder of `steps` steps with the assignment for `x0`.
+fn ladder_r1cs(x0: Scalar, steps: usize) -> R1cs {
+  let mut cs = R1csBuilder::new();
+  let five = Scalar::from(ladder::STEP_CONSTANT);
+  let mut x_val = x0;
+  let mut x = cs.alloc(x_val);
+  for i in 0..steps {
+    let s_val = x_val * x_val;
+    let s = cs.alloc(s_val);
+    x_val = s_val * x_val + x_val + five;
+    let next = if i + 1 == steps { cs.alloc_input(x_val) } else { cs.alloc(x_val) };
+
+    // x_i * x_i - s_i = 0
+    cs.constrain(x, x, s);
+    // s_i * x_i - (x_{i+1} - x_i - 5) = 0
+    cs.constrain(s, x, next - x - five);
+    x = next;
+  }
+  assert_eq!(cs.first_unsatisfied(), None, "should be satisfied");
+
+  cs.build()
+}
+
+fn main() {
+  let log_sizes = ladder::log_sizes_from_args();
+  let options = mode::Options::from_args(b"cubic_ladder");
+  for m in mode::modes_from_args() {
+    let name = format!("cubic_ladder ({})", m.name());
+    ladder::sweep(&name, "constraints", &log_sizes, |steps| {
+      mode::prove_and_verify(m, "cubic_ladder", produce_r1cs(steps), &options).run
+    });
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use libspartan::{InputsAssignment, NIZKGens, NIZK};
+  use labkit::context::ProofContext;
+
+  /// Computes `x_steps` natively.
+  fn ladder_output(x0: Scalar, steps: usize) -> Scalar {
+    (0..steps).fold(x0, |x, _| x * x * x + x + Scalar::from(ladder::STEP_CONSTANT))
+  }
+
+  #[test]
+  fn test_ladder_proves_its_output() {
+    let steps = ladder::steps(4);
+    let x0 = Scalar::from(3u64);
+    let r1cs = ladder_r1cs(x0, steps);
+    assert_eq!(r1cs.num_cons, 1 << 4);
+    let gens = NIZKGens::new(r1cs.num_cons, r1cs.num_vars, r1cs.num_inputs);
+    let context = ProofContext::new(b"cubic_ladder", "");
+    let proof = NIZK::prove(&r1cs.inst, r1cs.vars, &r1cs.inputs, &gens, &mut context.transcript());
+
+    let verify = |y: Scalar| {
+      let inputs = InputsAssignment::new(&[y.to_bytes()]).unwrap();
+      proof.verify(&r1cs.inst, &inputs, &mut context.transcript(), &gens).is_ok()
+    };
+    let y = ladder_output(x0, steps);
+    assert!(verify(y));
+    assert!(!verify(y + Scalar::ONE), "a wrong x_N should be rejected");
+  }
+}
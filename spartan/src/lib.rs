@@ -0,0 +1,4 @@
+pub mod mode;
+pub mod preprocessing;
+pub mod r1cs;
+pub mod range;
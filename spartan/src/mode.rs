@@ -0,0 +1,271 @@
+//! SNARK and NIZK modes.
+//!
+//! libspartan proves the same R1CS instance in two ways. `SNARK` preprocesses the
+//! instance: `SNARK::encode` commits to the A/B/C matrices once, and the verifier then
+//! works against that commitment in time sublinear in the instance. `NIZK` is
+//! transparent and needs no preprocessing, but its verifier reads the whole instance.
+//! The examples run both modes by default and report the encode time on its own, so
+//! the preprocessing cost can be weighed against the faster verifier. Pick a single mode
+//! with `--mode snark` or `--mode nizk`.
+//!
+//! SNARK mode can keep its generators and instance commitment on disk between runs
+//! (`--preprocessed <dir>`, see `crate::preprocessing`). Setup then only loads them,
+//! and the verifier reads the generators and commitment from the files.
+use std::time::{Duration, Instant};
+
+use libspartan::{NIZKGens, SNARKGens, NIZK, SNARK};
+
+use labkit::bench::{PhaseTimes, SizedRun};
+use labkit::{corpus, memory, rng};
+
+use labkit::context::ProofContext;
+use crate::preprocessing::{self, PreprocessingDir, ProverPreprocessing};
+use crate::r1cs::R1cs;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    Snark,
+    Nizk,
+}
+
+impl Mode {
+    pub const ALL: [Mode; 2] = [Mode::Snark, Mode::Nizk];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Mode::Snark => "snark",
+            Mode::Nizk => "nizk",
+        }
+    }
+}
+
+/// Parses `--mode snark|nizk|both` from the command line, defaulting to both.
+pub fn modes_from_args() -> Vec<Mode> {
+    let args: Vec<String> = std::env::args().collect();
+    let Some(mode) = args
+        .iter()
+        .position(|a| a == "--mode")
+        .map(|pos| args.get(pos + 1).expect("--mode needs a value"))
+    else {
+        return Mode::ALL.to_vec();
+    };
+    match mode.as_str() {
+        "snark" => vec![Mode::Snark],
+        "nizk" => vec![Mode::Nizk],
+        "both" => Mode::ALL.to_vec(),
+        other => panic!("unknown mode {:?}, expected snark, nizk or both", other),
+    }
+}
+
+/// Settings shared by every run of an example.
+pub struct Options {
+    pub context: ProofContext,
+    pub preprocessed: Option<PreprocessingDir>,
+}
+
+impl Options {
+    /// Reads `--context` and `--preprocessed` for an example using `domain`, and
+    /// refuses `--seed`.
+    pub fn from_args(domain: &'static [u8]) -> Self {
+        rng::refuse_seed("libspartan seeds the prover's random tape from OsRng, so a seed could not fix its proof");
+        Options {
+            context: ProofContext::from_args(domain),
+            preprocessed: PreprocessingDir::from_args(),
+        }
+    }
+}
+
+/// Costs of proving one instance in one mode.
+#[derive(Clone, Copy, Debug)]
+pub struct ModeRun {
+    pub mode: Mode,
+    /// Time spent in `SNARK::encode`, included in `run.times.setup`. Zero for NIZK and
+    /// for preprocessing loaded from disk.
+    pub encode: Duration,
+    pub run: SizedRun,
+}
+
+/// Runs setup, prove and verify once in `mode`. `name` identifies the example for
+/// saved preprocessing and fuzz corpus seeds.
+pub fn prove_and_verify(mode: Mode, name: &str, r1cs: R1cs, options: &Options) -> ModeRun {
+    let key = preprocessing::key(name, &r1cs);
+    let context = &options.context;
+    let R1cs {
+        num_cons,
+        num_vars,
+        num_inputs,
+        num_non_zero_entries,
+        inst,
+        vars,
+        inputs,
+        ..
+    } = r1cs;
+    println!("Mode: {}, number of constraints: {}", mode.name(), num_cons);
+
+    let (encode, times, proof_size, prove_memory) = match mode {
+        Mode::Snark => {
+            let start0 = Instant::now();
+            let ((pre, encode), _) = memory::measure("Setup", || {
+                let saved = options
+                    .preprocessed
+                    .as_ref()
+                    .and_then(|dir| dir.load_prover(&key).expect("cannot load the saved preprocessing"));
+                if let Some(pre) = saved {
+                    println!("Loaded generators and commitment for {}", key);
+                    return (pre, None);
+                }
+                // produce public parameters
+                let gens = SNARKGens::new(num_cons, num_vars, num_inputs, num_non_zero_entries);
+                // create a commitment to the R1CS instance
+                let start = Instant::now();
+                let (comm, decomm) = SNARK::encode(&inst, &gens);
+                (ProverPreprocessing { gens, comm, decomm }, Some(start.elapsed()))
+            });
+            let setup = start0.elapsed();
+            if let (Some(dir), Some(_)) = (&options.preprocessed, encode) {
+                dir.save(&key, &pre).expect("cannot save preprocessing");
+                println!("Saved generators and commitment for {}", key);
+            }
+
+            let start1 = Instant::now();
+            let (proof, prove_memory) = memory::measure("Prove", || {
+                SNARK::prove(
+                    &inst,
+                    &pre.comm,
+                    &pre.decomm,
+                    vars,
+                    &inputs,
+                    &pre.gens,
+                    &mut context.transcript(),
+                )
+            });
+            let prove = start1.elapsed();
+            let proof_bytes = bincode::serialize(&proof).expect("Serialization failed");
+            let proof_size = proof_bytes.len();
+            // `--save-corpus <dir>`: seed the fuzz target with this proof
+            corpus::save_from_args("snark", name, &proof_bytes);
+
+            // With saved preprocessing, the verifier reads only the generators and the
+            // commitment from disk
+            let verifier_files = options
+                .preprocessed
+                .as_ref()
+                .map(|dir| dir.load_verifier(&key).expect("cannot load the commitment"));
+            let (gens, comm) = match &verifier_files {
+                Some((gens, comm)) => (gens, comm),
+                None => (&pre.gens, &pre.comm),
+            };
+            let start2 = Instant::now();
+            let (result, _) = memory::measure("Verify", || {
+                proof.verify(comm, &inputs, &mut context.transcript(), gens)
+            });
+            let verify = start2.elapsed();
+            assert!(result.is_ok(), "proof should verify");
+
+            (encode.unwrap_or_default(), PhaseTimes { setup, prove, verify }, proof_size, prove_memory)
+        }
+        Mode::Nizk => {
+            let start0 = Instant::now();
+            let (gens, _) = memory::measure("Setup", || NIZKGens::new(num_cons, num_vars, num_inputs));
+            let setup = start0.elapsed();
+
+            let start1 = Instant::now();
+            let (proof, prove_memory) = memory::measure("Prove", || {
+                NIZK::prove(&inst, vars, &inputs, &gens, &mut context.transcript())
+            });
+            let prove = start1.elapsed();
+            let proof_size = bincode::serialize(&proof).expect("Serialization failed").len();
+
+            let start2 = Instant::now();
+            let (result, _) = memory::measure("Verify", || {
+                proof.verify(&inst, &inputs, &mut context.transcript(), &gens)
+            });
+            let verify = start2.elapsed();
+            assert!(result.is_ok(), "proof should verify");
+
+            (Duration::ZERO, PhaseTimes { setup, prove, verify }, proof_size, prove_memory)
+        }
+    };
+
+    println!("Encode time: {:.3} ms", encode.as_secs_f64() * 1000.0);
+    println!("Prove time: {:.3} ms", times.prove.as_secs_f64() * 1000.0);
+    println!("Verify time: {:.3} ms", times.verify.as_secs_f64() * 1000.0);
+    println!("Serialized proof size: {} bytes", proof_size);
+    println!("Proof verification successful!");
+
+    ModeRun {
+        mode,
+        encode,
+        run: SizedRun {
+            size: num_cons,
+            times,
+            proof_size,
+            prove_memory,
+        },
+    }
+}
+
+/// Checks bincode-encoded proofs of one instance. Bytes that do not decode are rejected.
+pub type EncodedVerifier = Box<dyn Fn(&[u8]) -> bool>;
+
+/// Proves `r1cs` in `mode`, untimed, and returns the bincode-encoded proof with a
+/// verifier for encoded proofs of the same instance and public inputs. The tests that
+/// tamper with serialized proofs and the `snark` fuzz target use it.
+pub fn prove_encoded(mode: Mode, r1cs: R1cs, context: &ProofContext) -> (Vec<u8>, EncodedVerifier) {
+    let context = context.clone();
+    let R1cs {
+        num_cons,
+        num_vars,
+        num_inputs,
+        num_non_zero_entries,
+        inst,
+        vars,
+        inputs,
+        ..
+    } = r1cs;
+    match mode {
+        Mode::Snark => {
+            let gens = SNARKGens::new(num_cons, num_vars, num_inputs, num_non_zero_entries);
+            let (comm, decomm) = SNARK::encode(&inst, &gens);
+            let proof = SNARK::prove(&inst, &comm, &decomm, vars, &inputs, &gens, &mut context.transcript());
+            let bytes = bincode::serialize(&proof).expect("Serialization failed");
+            let verify = move |bytes: &[u8]| {
+                bincode::deserialize::<SNARK>(bytes)
+                    .is_ok_and(|proof| proof.verify(&comm, &inputs, &mut context.transcript(), &gens).is_ok())
+            };
+            (bytes, Box::new(verify))
+        }
+        Mode::Nizk => {
+            let gens = NIZKGens::new(num_cons, num_vars, num_inputs);
+            let proof = NIZK::prove(&inst, vars, &inputs, &gens, &mut context.transcript());
+            let bytes = bincode::serialize(&proof).expect("Serialization failed");
+            let verify = move |bytes: &[u8]| {
+                bincode::deserialize::<NIZK>(bytes)
+                    .is_ok_and(|proof| proof.verify(&inst, &inputs, &mut context.transcript(), &gens).is_ok())
+            };
+            (bytes, Box::new(verify))
+        }
+    }
+}
+
+/// Prints the runs of one instance side by side. Setup includes the encode time.
+pub fn print_comparison(name: &str, runs: &[ModeRun]) {
+    let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+    println!("\n{}: SNARK vs NIZK, times in ms", name);
+    println!(
+        "{:<6} {:>12} {:>12} {:>12} {:>12} {:>12}",
+        "mode", "setup", "encode", "prove", "verify", "proof size"
+    );
+    for r in runs {
+        let t = r.run.times;
+        println!(
+            "{:<6} {:>12.3} {:>12.3} {:>12.3} {:>12.3} {:>12}",
+            r.mode.name(),
+            ms(t.setup),
+            ms(r.encode),
+            ms(t.prove),
+            ms(t.verify),
+            r.run.proof_size
+        );
+    }
+}
@@ -0,0 +1,157 @@
+//! Saving SNARK generators and instance commitments for reuse.
+//!
+//! `SNARKGens::new` and `SNARK::encode` depend only on the instance, yet they run on
+//! every invocation and dominate the runtime of large instances. With
+//! `--preprocessed <dir>`, SNARK mode keeps them in `<dir>/<key>/` as bincode files:
+//! - `gens.bin`: the public parameters, needed by prover and verifier
+//! - `commitment.bin`: the instance commitment, all the verifier needs besides the gens
+//! - `decommitment.bin`: the prover's opening of the commitment
+//!
+//! A run whose files already exist loads them instead of recomputing them. The key must
+//! identify the instance: a commitment to a different instance makes every proof fail
+//! to verify. `key` therefore appends the instance sizes and a hash of the A/B/C
+//! matrices to the example's name, so instances of the same shape do not share files.
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use libspartan::{ComputationCommitment, ComputationDecommitment, SNARKGens};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::r1cs::R1cs;
+
+pub const GENS_FILE: &str = "gens.bin";
+pub const COMMITMENT_FILE: &str = "commitment.bin";
+pub const DECOMMITMENT_FILE: &str = "decommitment.bin";
+
+/// Directory holding saved preprocessing, one subdirectory per instance.
+#[derive(Clone, Debug)]
+pub struct PreprocessingDir {
+    root: PathBuf,
+}
+
+/// Everything the SNARK prover needs besides the instance and witness.
+pub struct ProverPreprocessing {
+    pub gens: SNARKGens,
+    pub comm: ComputationCommitment,
+    pub decomm: ComputationDecommitment,
+}
+
+/// Name of the subdirectory for `r1cs` in the example `name`: the sizes and the first
+/// 8 bytes of the instance digest in hex.
+pub fn key(name: &str, r1cs: &R1cs) -> String {
+    let digest: String = r1cs.digest[..8].iter().map(|b| format!("{:02x}", b)).collect();
+    format!(
+        "{}_{}x{}x{}_{}_{}",
+        name, r1cs.num_cons, r1cs.num_vars, r1cs.num_inputs, r1cs.num_non_zero_entries, digest
+    )
+}
+
+fn save<T: Serialize>(path: &Path, value: &T) -> io::Result<()> {
+    let bytes = bincode::serialize(value).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    fs::write(path, bytes)
+}
+
+fn load<T: DeserializeOwned>(path: &Path) -> io::Result<T> {
+    let bytes = fs::read(path)?;
+    bincode::deserialize(&bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+impl PreprocessingDir {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        PreprocessingDir { root: root.into() }
+    }
+
+    /// Parses `--preprocessed <dir>`. Returns `None` if the flag is absent.
+    pub fn from_args() -> Option<Self> {
+        let args: Vec<String> = std::env::args().collect();
+        let pos = args.iter().position(|a| a == "--preprocessed")?;
+        let dir = args.get(pos + 1).expect("--preprocessed needs a directory");
+        Some(Self::new(dir))
+    }
+
+    fn file(&self, key: &str, name: &str) -> PathBuf {
+        self.root.join(key).join(name)
+    }
+
+    /// Writes all three files for `key`.
+    pub fn save(&self, key: &str, preprocessing: &ProverPreprocessing) -> io::Result<()> {
+        fs::create_dir_all(self.root.join(key))?;
+        save(&self.file(key, GENS_FILE), &preprocessing.gens)?;
+        save(&self.file(key, COMMITMENT_FILE), &preprocessing.comm)?;
+        save(&self.file(key, DECOMMITMENT_FILE), &preprocessing.decomm)
+    }
+
+    /// Loads the prover's files for `key`, or `None` if any of them is missing. Files
+    /// that exist but cannot be read or decoded are an error.
+    pub fn load_prover(&self, key: &str) -> io::Result<Option<ProverPreprocessing>> {
+        let load_all = || -> io::Result<ProverPreprocessing> {
+            Ok(ProverPreprocessing {
+                gens: load(&self.file(key, GENS_FILE))?,
+                comm: load(&self.file(key, COMMITMENT_FILE))?,
+                decomm: load(&self.file(key, DECOMMITMENT_FILE))?,
+            })
+        };
+        match load_all() {
+            Ok(preprocessing) => Ok(Some(preprocessing)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Loads what a verifier needs for `key`: the generators and the commitment,
+    /// without the decommitment.
+    pub fn load_verifier(&self, key: &str) -> io::Result<(SNARKGens, ComputationCommitment)> {
+        Ok((load(&self.file(key, GENS_FILE))?, load(&self.file(key, COMMITMENT_FILE))?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::r1cs;
+    use labkit::context::ProofContext;
+    use libspartan::SNARK;
+
+    #[test]
+    fn test_key_depends_on_the_matrices() {
+        let (first, second) = (r1cs::synthetic(16, 16, 2, 1), r1cs::synthetic(16, 16, 2, 2));
+        let shape = |r: &R1cs| (r.num_cons, r.num_vars, r.num_inputs, r.num_non_zero_entries);
+        assert_eq!(shape(&first), shape(&second));
+        assert_ne!(key("synthetic", &first), key("synthetic", &second));
+        assert_eq!(key("synthetic", &first), key("synthetic", &r1cs::synthetic(16, 16, 2, 1)));
+    }
+
+    #[test]
+    fn test_saved_preprocessing_proves_and_verifies() {
+        let root = std::env::temp_dir().join(format!("spartan-preprocessing-{}", std::process::id()));
+        let dir = PreprocessingDir::new(&root);
+        let r1cs = r1cs::synthetic(16, 16, 2, 1);
+        let key = key("synthetic", &r1cs);
+        assert!(dir.load_prover(&key).unwrap().is_none());
+
+        let gens = SNARKGens::new(r1cs.num_cons, r1cs.num_vars, r1cs.num_inputs, r1cs.num_non_zero_entries);
+        let (comm, decomm) = SNARK::encode(&r1cs.inst, &gens);
+        dir.save(&key, &ProverPreprocessing { gens, comm, decomm }).unwrap();
+
+        let pre = dir.load_prover(&key).unwrap().expect("the files were just saved");
+        let context = ProofContext::new(b"preprocessing", "");
+        let proof = SNARK::prove(
+            &r1cs.inst,
+            &pre.comm,
+            &pre.decomm,
+            r1cs.vars,
+            &r1cs.inputs,
+            &pre.gens,
+            &mut context.transcript(),
+        );
+        let (gens, comm) = dir.load_verifier(&key).unwrap();
+        assert!(proof.verify(&comm, &r1cs.inputs, &mut context.transcript(), &gens).is_ok());
+
+        // A file that exists but does not decode is an error, not a cache miss
+        fs::write(dir.file(&key, DECOMMITMENT_FILE), b"corrupt").unwrap();
+        assert!(dir.load_prover(&key).is_err());
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,360 @@
+//! A small builder for Spartan R1CS instances.
+//!
+//! Spartan takes an instance as three sparse matrices over `z = (vars, 1, inputs)`,
+//! given as `(row, column, value)` triples. Writing those by hand means tracking
+//! column offsets for the constant and the inputs and guessing the number of non-zero
+//! entries. `R1csBuilder` allocates witness variables and public inputs together with
+//! their values and takes constraints of the form `a * b = c` over linear
+//! combinations. `build` then lays out the columns, pads the sizes to the powers of two
+//! Spartan works with, counts the non-zero entries exactly and returns the instance
+//! with its satisfying assignment.
+//!
+//! ```ignore
+//! let mut cs = R1csBuilder::new();
+//! let x = cs.alloc(Scalar::from(3u64));
+//! let x_sq = cs.alloc(Scalar::from(9u64));
+//! cs.constrain(x, x, x_sq);
+//! let r1cs = cs.build();
+//! ```
+use std::collections::BTreeMap;
+use std::ops::{Add, Mul, Neg, Sub};
+
+use curve25519_dalek::scalar::Scalar;
+use libspartan::{InputsAssignment, Instance, VarsAssignment};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use sha2::{Digest, Sha256};
+
+/// An R1CS instance with a satisfying assignment and the sizes the generators need.
+pub struct R1cs {
+    pub num_cons: usize,
+    pub num_vars: usize,
+    pub num_inputs: usize,
+    pub num_non_zero_entries: usize,
+    /// SHA-256 of the sizes and the A/B/C matrices. `Instance` keeps its own digest
+    /// private, so saved preprocessing is matched to its instance with this one.
+    pub digest: [u8; 32],
+    pub inst: Instance,
+    pub vars: VarsAssignment,
+    pub inputs: InputsAssignment,
+}
+
+/// A column of `z`: a witness variable, the constant 1 or a public input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Variable {
+    Witness(usize),
+    One,
+    Input(usize),
+}
+
+/// A sum of variables with coefficients.
+#[derive(Clone, Debug, Default)]
+pub struct LinearCombination {
+    terms: Vec<(Variable, Scalar)>,
+}
+
+impl LinearCombination {
+    /// Terms with equal variables merged and zero coefficients dropped.
+    fn simplified(&self) -> BTreeMap<Variable, Scalar> {
+        let mut merged = BTreeMap::new();
+        for &(var, coeff) in &self.terms {
+            *merged.entry(var).or_insert(Scalar::ZERO) += coeff;
+        }
+        merged.retain(|_, coeff| *coeff != Scalar::ZERO);
+        merged
+    }
+}
+
+impl From<Variable> for LinearCombination {
+    fn from(var: Variable) -> Self {
+        LinearCombination {
+            terms: vec![(var, Scalar::ONE)],
+        }
+    }
+}
+
+impl From<Scalar> for LinearCombination {
+    fn from(constant: Scalar) -> Self {
+        LinearCombination {
+            terms: vec![(Variable::One, constant)],
+        }
+    }
+}
+
+impl<T: Into<LinearCombination>> Add<T> for LinearCombination {
+    type Output = LinearCombination;
+
+    fn add(mut self, rhs: T) -> LinearCombination {
+        self.terms.extend(rhs.into().terms);
+        self
+    }
+}
+
+impl<T: Into<LinearCombination>> Sub<T> for LinearCombination {
+    type Output = LinearCombination;
+
+    fn sub(self, rhs: T) -> LinearCombination {
+        self + -rhs.into()
+    }
+}
+
+impl Neg for LinearCombination {
+    type Output = LinearCombination;
+
+    fn neg(self) -> LinearCombination {
+        self * -Scalar::ONE
+    }
+}
+
+impl Mul<Scalar> for LinearCombination {
+    type Output = LinearCombination;
+
+    fn mul(mut self, rhs: Scalar) -> LinearCombination {
+        for (_, coeff) in &mut self.terms {
+            *coeff *= rhs;
+        }
+        self
+    }
+}
+
+impl<T: Into<LinearCombination>> Add<T> for Variable {
+    type Output = LinearCombination;
+
+    fn add(self, rhs: T) -> LinearCombination {
+        LinearCombination::from(self) + rhs
+    }
+}
+
+impl<T: Into<LinearCombination>> Sub<T> for Variable {
+    type Output = LinearCombination;
+
+    fn sub(self, rhs: T) -> LinearCombination {
+        LinearCombination::from(self) - rhs
+    }
+}
+
+impl Mul<Scalar> for Variable {
+    type Output = LinearCombination;
+
+    fn mul(self, rhs: Scalar) -> LinearCombination {
+        LinearCombination::from(self) * rhs
+    }
+}
+
+/// Collects variables, inputs and constraints together with the witness.
+#[derive(Default)]
+pub struct R1csBuilder {
+    vars: Vec<Scalar>,
+    inputs: Vec<Scalar>,
+    constraints: Vec<(LinearCombination, LinearCombination, LinearCombination)>,
+}
+
+impl R1csBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allocates a private witness variable holding `value`.
+    pub fn alloc(&mut self, value: Scalar) -> Variable {
+        self.vars.push(value);
+        Variable::Witness(self.vars.len() - 1)
+    }
+
+    /// Allocates a public input holding `value`.
+    pub fn alloc_input(&mut self, value: Scalar) -> Variable {
+        self.inputs.push(value);
+        Variable::Input(self.inputs.len() - 1)
+    }
+
+    /// Adds the constraint `a * b = c`.
+    pub fn constrain(
+        &mut self,
+        a: impl Into<LinearCombination>,
+        b: impl Into<LinearCombination>,
+        c: impl Into<LinearCombination>,
+    ) {
+        self.constraints.push((a.into(), b.into(), c.into()));
+    }
+
+    /// The value of `lc` under the current assignment.
+    pub fn eval(&self, lc: &LinearCombination) -> Scalar {
+        lc.terms
+            .iter()
+            .map(|&(var, coeff)| {
+                coeff
+                    * match var {
+                        Variable::Witness(i) => self.vars[i],
+                        Variable::One => Scalar::ONE,
+                        Variable::Input(i) => self.inputs[i],
+                    }
+            })
+            .sum()
+    }
+
+    /// Index of the first constraint the assignment violates, if any.
+    pub fn first_unsatisfied(&self) -> Option<usize> {
+        self.constraints
+            .iter()
+            .position(|(a, b, c)| self.eval(a) * self.eval(b) != self.eval(c))
+    }
+
+    /// Number of constraints added so far, before padding.
+    pub fn num_constraints(&self) -> usize {
+        self.constraints.len()
+    }
+
+    /// Lays out the matrices and the assignment. The witness is padded with zeros to a
+    /// power of two that is larger than the number of inputs, and the constraint count
+    /// to a power of two of at least 2, so Spartan does not have to remap any columns.
+    #[allow(non_snake_case)]
+    pub fn build(self) -> R1cs {
+        let num_inputs = self.inputs.len();
+        let num_vars = self.vars.len().max(num_inputs + 1).next_power_of_two();
+        let num_cons = self.constraints.len().max(2).next_power_of_two();
+
+        // z = (vars, 1, inputs)
+        let column = |var: Variable| match var {
+            Variable::Witness(i) => i,
+            Variable::One => num_vars,
+            Variable::Input(i) => num_vars + 1 + i,
+        };
+        let mut A: Vec<(usize, usize, [u8; 32])> = Vec::new();
+        let mut B: Vec<(usize, usize, [u8; 32])> = Vec::new();
+        let mut C: Vec<(usize, usize, [u8; 32])> = Vec::new();
+        for (row, (a, b, c)) in self.constraints.iter().enumerate() {
+            for (matrix, lc) in [(&mut A, a), (&mut B, b), (&mut C, c)] {
+                for (var, coeff) in lc.simplified() {
+                    matrix.push((row, column(var), coeff.to_bytes()));
+                }
+            }
+        }
+        let num_non_zero_entries = A.len().max(B.len()).max(C.len());
+
+        let mut hasher = Sha256::new();
+        for size in [num_cons, num_vars, num_inputs] {
+            hasher.update((size as u64).to_le_bytes());
+        }
+        for matrix in [&A, &B, &C] {
+            hasher.update((matrix.len() as u64).to_le_bytes());
+            for (row, col, value) in matrix {
+                hasher.update((*row as u64).to_le_bytes());
+                hasher.update((*col as u64).to_le_bytes());
+                hasher.update(value);
+            }
+        }
+
+        let inst = Instance::new(num_cons, num_vars, num_inputs, &A, &B, &C)
+            .expect("the builder only produces valid indices");
+        let mut vars: Vec<[u8; 32]> = self.vars.iter().map(Scalar::to_bytes).collect();
+        vars.resize(num_vars, Scalar::ZERO.to_bytes());
+        let inputs: Vec<[u8; 32]> = self.inputs.iter().map(Scalar::to_bytes).collect();
+
+        R1cs {
+            num_cons,
+            num_vars,
+            num_inputs,
+            num_non_zero_entries,
+            digest: hasher.finalize().into(),
+            inst,
+            vars: VarsAssignment::new(&vars).expect("assignments are canonical scalars"),
+            inputs: InputsAssignment::new(&inputs).expect("assignments are canonical scalars"),
+        }
+    }
+}
+
+/// A satisfiable instance with random structure, shaped like the synthetic instances of
+/// `Instance::produce_synthetic_r1cs`: constraint `i` multiplies columns `i` and `i + 2`
+/// of `z` and compares the product to a multiple of column `i + 3`, so every matrix
+/// has one entry per row. Unlike the library's generator, this one is seeded, so the
+/// same arguments always give the same instance and its preprocessing can be reused.
+pub fn synthetic(num_cons: usize, num_vars: usize, num_inputs: usize, seed: u64) -> R1cs {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut cs = R1csBuilder::new();
+    // z = (vars, 1, inputs), as Spartan lays it out
+    let mut z: Vec<(Variable, Scalar)> = Vec::with_capacity(num_vars + 1 + num_inputs);
+    for _ in 0..num_vars {
+        let value = Scalar::random(&mut rng);
+        z.push((cs.alloc(value), value));
+    }
+    z.push((Variable::One, Scalar::ONE));
+    for _ in 0..num_inputs {
+        let value = Scalar::random(&mut rng);
+        z.push((cs.alloc_input(value), value));
+    }
+
+    for i in 0..num_cons {
+        let (a, a_val) = z[i % z.len()];
+        let (b, b_val) = z[(i + 2) % z.len()];
+        let (c, c_val) = match z[(i + 3) % z.len()] {
+            (_, value) if value == Scalar::ZERO => (Variable::One, Scalar::ONE),
+            entry => entry,
+        };
+        cs.constrain(a, b, c * (a_val * b_val * c_val.invert()));
+    }
+    cs.build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `x^3 + x + 5 = y` as in the cubic example.
+    fn cubic(x: u64, y: u64) -> R1csBuilder {
+        let x = Scalar::from(x);
+        let mut cs = R1csBuilder::new();
+        let z0 = cs.alloc(x);
+        let z1 = cs.alloc(x * x);
+        let z2 = cs.alloc(x * x * x);
+        let y = cs.alloc_input(Scalar::from(y));
+        cs.constrain(z0, z0, z1);
+        cs.constrain(z1, z0, z2);
+        cs.constrain(z2 + z0 + Scalar::from(5u64), Scalar::ONE, y);
+        cs
+    }
+
+    #[test]
+    fn test_sizes_and_satisfiability() {
+        let cs = cubic(3, 35);
+        assert_eq!(cs.first_unsatisfied(), None);
+        let r1cs = cs.build();
+        assert_eq!(r1cs.num_cons, 4);
+        assert_eq!(r1cs.num_vars, 4);
+        assert_eq!(r1cs.num_inputs, 1);
+        // A has z0, z1, z2 + z0 + 5 (three terms)
+        assert_eq!(r1cs.num_non_zero_entries, 5);
+        assert!(r1cs.inst.is_sat(&r1cs.vars, &r1cs.inputs).unwrap());
+    }
+
+    #[test]
+    fn test_unsatisfied_assignment() {
+        let cs = cubic(3, 36);
+        assert_eq!(cs.first_unsatisfied(), Some(2));
+        let r1cs = cs.build();
+        assert!(!r1cs.inst.is_sat(&r1cs.vars, &r1cs.inputs).unwrap());
+    }
+
+    #[test]
+    fn test_terms_are_merged() {
+        let mut cs = R1csBuilder::new();
+        let x = cs.alloc(Scalar::from(2u64));
+        cs.constrain(x + x - x, Scalar::ONE, x);
+        cs.constrain(x - x + Scalar::ONE, x, x);
+        let r1cs = cs.build();
+        // A: x in row 0, the constant in row 1
+        assert_eq!(r1cs.num_non_zero_entries, 2);
+    }
+
+    #[test]
+    fn test_synthetic_is_deterministic() {
+        let r1cs = synthetic(256, 64, 8, 7);
+        assert_eq!((r1cs.num_cons, r1cs.num_vars, r1cs.num_inputs), (256, 64, 8));
+        assert_eq!(r1cs.num_non_zero_entries, 256);
+        assert!(r1cs.inst.is_sat(&r1cs.vars, &r1cs.inputs).unwrap());
+
+        // Same seed, same matrices: the first witness satisfies the second instance
+        let again = synthetic(256, 64, 8, 7);
+        assert!(again.inst.is_sat(&r1cs.vars, &r1cs.inputs).unwrap());
+        let other = synthetic(256, 64, 8, 8);
+        assert!(!other.inst.is_sat(&r1cs.vars, &r1cs.inputs).unwrap());
+    }
+}
@@ -1,51 +1,91 @@
+//! A synthetic R1CS instance with the size of a SHA-256 circuit.
+//!
+//! Spartan has no SHA-256 gadget, so this example does not hash anything. It proves a
+//! random satisfiable instance with as many constraints as SHA-256 over the given
+//! number of blocks, to estimate what a real SHA-256 circuit would cost.
 extern crate libspartan;
 extern crate merlin;
-use std::time::Instant;
 
-use libspartan::{Instance, SNARKGens, SNARK};
-use merlin::Transcript;
+use labkit::bench;
+use spartan_lab::mode;
+use spartan_lab::r1cs::{self, R1cs};
+use labkit::scaling;
+
+// Counts the heap usage that `memory::measure` reports
+#[global_allocator]
+static ALLOCATOR: labkit::memory::PeakAllocator = labkit::memory::PeakAllocator;
 fn main() {
+    let modes = mode::modes_from_args();
+    let options = mode::Options::from_args(b"snark_example");
+    if let Some(config) = bench::BenchConfig::from_args() {
+        for m in modes {
+            bench::run(&format!("sha256_{}", m.name()), &config, || {
+                mode::prove_and_verify(m, "sha256", synthetic_r1cs(1), &options).run.times
+            });
+        }
+    } else if let Some(lengths) = scaling::input_lengths_from_args() {
+        let lengths = instance_lengths(&lengths);
+        for m in modes {
+            let name = format!("sha256 (synthetic, {})", m.name());
+            scaling::input_size_sweep(&name, "constraints", &lengths, |len| {
+                mode::prove_and_verify(m, "sha256", synthetic_r1cs(scaling::sha256_blocks(len)), &options).run
+            });
+        }
+    } else if let Some(counts) = scaling::thread_counts_from_args() {
+        for m in modes {
+            scaling::sweep(&format!("sha256_{}", m.name()), &counts, || {
+                mode::prove_and_verify(m, "sha256", synthetic_r1cs(1), &options).run.times.prove
+            });
+        }
+    } else {
+        let runs: Vec<_> = modes
+            .into_iter()
+            .map(|m| mode::prove_and_verify(m, "sha256", synthetic_r1cs(1), &options))
+            .collect();
+        mode::print_comparison("sha256", &runs);
+    }
+}
+
+/// The message lengths to sweep for `lengths`. The instance's block count is rounded up
+/// to a power of two, so lengths that round to the same count share one instance. Each
+/// instance is run once, at the longest message of its block count, so that the sweep
+/// table shows the number of blocks actually proven.
+fn instance_lengths(lengths: &[usize]) -> Vec<usize> {
+    let mut blocks = Vec::new();
+    for &len in lengths {
+        let rounded = scaling::sha256_blocks(len).next_power_of_two();
+        println!(
+            "{}-byte input: {} block(s), proven as an instance of {}",
+            len,
+            scaling::sha256_blocks(len),
+            rounded
+        );
+        blocks.push(rounded);
+    }
+    blocks.sort_unstable();
+    blocks.dedup();
+    blocks.into_iter().map(|b| 64 * b - 9).collect()
+}
+
+/// Seed of the synthetic instance.
+const SYNTHETIC_SEED: u64 = 256;
+
+/// A synthetic instance the size of a `blocks`-block SHA-256.
+fn synthetic_r1cs(blocks: usize) -> R1cs {
     // specify the size of an R1CS instance
     // Since the Spartan library does not 
     // provide relevant gadgets for the 
     // sha256 circuit, we use a synthetic 
     //example to estimate the circuit size.
-    let num_vars = 1024;
-    let num_cons = 32768;
+    // One compression takes roughly 2^15 constraints; Spartan pads the instance to
+    // a power of two, so the block count is rounded up the same way.
+    let scale = blocks.next_power_of_two();
+    let num_vars = 1024 * scale;
+    let num_cons = 32768 * scale;
     let num_inputs = 512;
-    let num_non_zero_entries = 32768;
-
-    // produce public parameters
-    let gens = SNARKGens::new(num_cons, num_vars, num_inputs, num_non_zero_entries);
-
-    // ask the library to produce a synthentic R1CS instance
-    let (inst, vars, inputs) = Instance::produce_synthetic_r1cs(num_cons, num_vars, num_inputs);
-
-    // create a commitment to the R1CS instance
-    let (comm, decomm) = SNARK::encode(&inst, &gens);
-
-    // produce a proof of satisfiability
-    let mut prover_transcript = Transcript::new(b"snark_example");
-    let start1 = Instant::now();
-    let proof = SNARK::prove(&inst, &comm, &decomm, vars, &inputs, &gens, &mut prover_transcript);
-
-    let serialized_proof = bincode::serialize(&proof).expect("Serialization failed");
-
-
-    println!("Serialized proof size: {} bytes", serialized_proof.len());
-    let start2 = Instant::now();
-    // verify the proof of satisfiability
-    let mut verifier_transcript = Transcript::new(b"snark_example");
-    assert!(proof
-      .verify(&comm, &inputs, &mut verifier_transcript, &gens)
-      .is_ok());
-    let start3 = Instant::now();
-    let duration1 = start2.duration_since(start1);
-    let duration2 = start3.duration_since(start2);
+    println!("Synthetic instance for {} block(s): {} constraints, {} variables", blocks, num_cons, num_vars);
 
-    let millis1 = duration1.as_secs_f64() * 1000.0;
-    let millis2 = duration2.as_secs_f64() * 1000.0;
-    println!("Prove time: {:.3} milliseconds", millis1);
-    println!("Verify time: {:.3} milliseconds", millis2);
-    println!("proof verification successful!");
-}
\ No newline at end of file
+    // a seeded synthetic instance, so that saved preprocessing (`--preprocessed`)
+    // matches it on the next run
+    r1cs::synthetic(num_cons, num_vars, num_inputs, SYNTHETIC_SEED)
+}